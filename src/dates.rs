@@ -21,12 +21,19 @@ pub fn month_first_and_last_dates(date: &NaiveDate) -> (NaiveDate, NaiveDate) {
     (start_date, end_date)
 }
 
+/// Parse month given as `YYYY-MM` into the first date of the month
+pub fn parse_month(month: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("'{}' is not a valid month, use YYYY-MM", month))
+}
+
 #[cfg(test)]
 mod tests {
     mod current_month_dates {
         use super::super::*;
         use chrono::NaiveDate;
         #[test]
+        #[allow(clippy::zero_prefixed_literal, reason = "dates are written as in YYYY-MM-DD")]
         fn given_day_in_middle_of_month() {
             let wednesday = &NaiveDate::from_ymd_opt(2022, 01, 12).unwrap();
             let (start_date, end_date) = month_first_and_last_dates(wednesday);
@@ -51,6 +58,7 @@ mod tests {
             assert_eq!(end_date, NaiveDate::from_ymd_opt(2022, 2, 28).unwrap());
         }
         #[test]
+        #[allow(clippy::zero_prefixed_literal, reason = "dates are written as in YYYY-MM-DD")]
         fn given_day_in_december() {
             let tuesday = NaiveDate::from_ymd_opt(2021, 12, 07).unwrap();
             let (start_date, end_date) = month_first_and_last_dates(&tuesday);
@@ -59,4 +67,17 @@ mod tests {
             assert_eq!(end_date, NaiveDate::from_ymd_opt(2021, 12, 31).unwrap());
         }
     }
+    mod months {
+        use super::super::*;
+        #[test]
+        fn parses_month() {
+            assert_eq!(
+                parse_month("2022-02"),
+                Ok(NaiveDate::from_ymd_opt(2022, 2, 1).unwrap())
+            );
+            assert!(parse_month("2022-13").is_err());
+            assert!(parse_month("2022-02-01").is_err());
+            assert!(parse_month("february").is_err());
+        }
+    }
 }
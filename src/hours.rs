@@ -1,30 +1,717 @@
+use crate::dates;
 use crate::integrations;
+use crate::projects;
 use crate::settings;
-use crate::strict_string::FilePath;
-use chrono::Local;
+use crate::strict_string::{ClientName, FilePath, ProjectHash};
+use chrono::{Local, NaiveDate};
+use std::io::IsTerminal;
+use std::path::PathBuf;
 use std::collections::HashMap;
+mod billing;
+mod calendar;
 mod table;
+mod export;
+mod filter;
+mod invoice;
+mod status;
 mod store;
+mod summary;
+mod timesheet;
+mod tui;
 use store::{Store, DiskStore};
+use rounding::Rounding;
+use target::TargetHours;
+pub use export::ExportFormat;
+pub use filter::{ClientFilters, Filters};
+pub use status::DEFAULT_FORMAT as DEFAULT_STATUS_FORMAT;
+pub mod layout;
+pub mod rounding;
+pub mod target;
 pub mod types;
 pub mod ui;
 
 
 
 static HOURS_FILENAME: &str = "hours.toml";
+static TIMESHEET_TEMPLATE_FILENAME: &str = "timesheet.html";
+static INVOICE_TEMPLATE_FILENAME: &str = "invoice.html";
+
+/// How rows are grouped in the hours table
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One row per project
+    Project,
+    /// Projects grouped under their client with a subtotal row
+    Client,
+}
+
+/// Output format of the monthly hours
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Table for humans
+    Table,
+    /// JSON document for scripts, see README for the schema
+    Json,
+}
+
+/// Options for showing the monthly hours
+#[derive(Debug)]
+pub struct ViewOptions {
+    pub group_by: GroupBy,
+    pub format: OutputFormat,
+    pub filters: Filters,
+    /// Columns of the table, overrides the columns in the settings
+    pub columns: Option<Vec<layout::Column>>,
+}
 
 /// Show your current monthly progress
-pub fn show_monthly_hours() {
+pub fn show_monthly_hours(options: &ViewOptions) {
+    let config = settings::load();
+    if config.no_input == Some(true) || options.format == OutputFormat::Json {
+        ui::disable_input();
+    }
+    let time_entries = if refresh_required(&config) {
+        refresh_hours()
+    } else {
+        stored_time_entries()
+    };
+    println!("{}", monthly_hours_output(config, options, time_entries));
+}
+
+/// Redraw the monthly hours in place every `interval` until interrupted. Hours are refreshed from
+/// the integrations when the refresh treshold has passed, otherwise running timers tick forward.
+/// When refreshing fails, stored hours are shown and refreshing is tried again on the next round
+pub fn watch_monthly_hours(options: &ViewOptions, interval: u64) {
+    ui::disable_input();
+    let mut stdout = std::io::stdout();
+    loop {
+        let config = settings::load();
+        let (time_entries, refresh_error) = if refresh_required(&config) {
+            match try_refresh_hours() {
+                Ok(time_entries) => (time_entries, None),
+                Err(err) => (stored_time_entries(), Some(err)),
+            }
+        } else {
+            (stored_time_entries(), None)
+        };
+        let output = monthly_hours_output(config, options, time_entries);
+        let result = crossterm::execute!(
+            stdout,
+            crossterm::cursor::MoveTo(0, 0),
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
+        );
+        if let Err(err) = result {
+            eprintln!("Error occured during redrawing hours: {}", err);
+            std::process::exit(1);
+        }
+        println!("{}", output);
+        println!(
+            "\nUpdated at {}, every {} seconds. Press Ctrl+C to stop",
+            Local::now().format("%H:%M:%S"),
+            interval
+        );
+        if let Some(err) = refresh_error {
+            eprintln!("Couldn't refresh hours, showing stored hours: {}", err);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+    }
+}
+
+fn monthly_hours_output(
+    mut config: settings::Config,
+    options: &ViewOptions,
+    time_entries: types::TimeEntries,
+) -> String {
+    let time_entries = time_entries.with_running_until(Local::now());
+    let project_configs =
+        ensure_and_get_projects_configs(&mut config, &time_entries.uniq_projects());
+    let time_entries = with_merged_projects(time_entries, &project_configs);
+    let time_entries = without_ignored_projects(time_entries, &project_configs);
+    let time_entries = options.filters.apply(time_entries);
+    if options.format == OutputFormat::Json {
+        let summary = summary::generate_summary(
+            &time_entries,
+            &project_configs,
+            &client_configs(&config),
+            default_rounding(&config),
+            &config.currency,
+            config.target_billable_percent,
+        );
+        return serde_json::to_string_pretty(&summary).unwrap();
+    }
+    let table_config = config.table.clone().unwrap_or_default();
+    let holidays = config.holidays.clone().unwrap_or_default();
+    let currency = config.currency.clone();
+    let table_options = table::TableOptions {
+        columns: options.columns.clone().or(table_config.columns),
+        duration_format: table_config.duration_format.unwrap_or_default(),
+        border: table_config.border.unwrap_or_default(),
+        currency: &currency,
+        target_billable_percent: config.target_billable_percent,
+        holidays: &holidays,
+        status_symbols: !ui::use_colors(),
+        today: Local::now().date_naive(),
+        default_rounding: default_rounding(&config),
+    };
+    let mut table = match options.group_by {
+        GroupBy::Project => table::generate_table(
+            &time_entries,
+            &project_configs,
+            &client_configs(&config),
+            &table_options,
+        ),
+        GroupBy::Client => {
+            let client_configs =
+                ensure_and_get_client_configs(&mut config, &time_entries.uniq_clients());
+            table::generate_client_table(
+                &time_entries,
+                &project_configs,
+                &client_configs,
+                &table_options,
+            )
+        }
+    };
+    ui::style_table(&mut table);
+    format!("\n{table}")
+}
+
+/// Print one-line status of the hours with the format. Uses only the stored hours, so hours are
+/// never refreshed and input is never asked
+pub fn show_status(format: &str) {
+    let (config, stored_entries) =
+        match try_load_settings().and_then(|config| Ok((config, try_stored_time_entries()?))) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                eprintln!("{}", err);
+                println!("?");
+                return;
+            }
+        };
+    let project_configs = project_configs(&config);
+    let today = Local::now().date_naive();
+    let month_start = dates::month_first_and_last_dates(&today).0;
+    let time_entries = types::TimeEntries {
+        entries: stored_entries
+            .entries
+            .into_iter()
+            .filter(|entry| entry.start.is_some_and(|start| start.date_naive() >= month_start))
+            .collect(),
+    }
+    .with_running_until(Local::now());
+    let time_entries = with_merged_projects(time_entries, &project_configs);
+    let time_entries = without_ignored_projects(time_entries, &project_configs);
+    match status::render(
+        format,
+        &time_entries,
+        &project_configs,
+        config.target_daily_hours,
+        &today,
+    ) {
+        Ok(status) => println!("{}", status),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Options for showing entries of a project
+#[derive(Debug)]
+pub struct ShowOptions {
+    /// Project name, "Client / Project" or project key
+    pub project: String,
+    /// First day of the month
+    pub month: NaiveDate,
+}
+
+/// Show project's entries of a month grouped by day and description
+pub fn show_project(options: &ShowOptions) {
+    let config = settings::load();
+    let (from, to) = dates::month_first_and_last_dates(&options.month);
+    let mut project_configs = project_configs(&config);
+    let time_entries = time_entries_between(&config, &from, &to);
+    let mut projects = time_entries.uniq_projects();
+    project_configs.migrate_legacy_keys(&projects);
+    let time_entries = with_merged_projects(time_entries, &project_configs);
+    // Projects which have only merged entries are found with their own name too
+    for project in time_entries.uniq_projects() {
+        if !projects.iter().any(|other| other.key == project.key) {
+            projects.push(project);
+        }
+    }
+    let key = match projects::find_project_key(&options.project, &projects, &project_configs) {
+        Ok(key) => project_configs.resolve_key(&key),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let entries: Vec<types::TimeEntry> = time_entries
+        .entries
+        .into_iter()
+        .filter(|entry| project_configs.resolve_key(&entry.project_key()) == key)
+        .collect();
+    let entry = match entries.first() {
+        Some(entry) => entry,
+        None => {
+            eprintln!(
+                "No hours found for project {} in {}",
+                options.project,
+                options.month.format("%Y-%m")
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let title = match &entry.client {
+        Some(client) => format!("{} / {}", client, entry.project),
+        None => entry.project.to_string(),
+    };
+    println!("\n{} {}", title, options.month.format("%Y-%m"));
+    for mut table in [
+        table::generate_daily_breakdown_table(&entries),
+        table::generate_description_breakdown_table(&entries),
+    ] {
+        ui::style_table(&mut table);
+        println!("\n{}", table);
+    }
+}
+
+/// Show calendar of the month with total hours of each day
+pub fn show_calendar(month: &NaiveDate) {
+    let config = settings::load();
+    let (from, to) = dates::month_first_and_last_dates(month);
+    let time_entries = prepared_entries_between(&config, &from, &to);
+    let project_configs = project_configs(&config);
+    let mut table = calendar::generate_calendar(
+        month,
+        &time_entries,
+        calendar::daily_target(config.target_daily_hours, &project_configs),
+        &config.holidays.clone().unwrap_or_default(),
+        &Local::now().date_naive(),
+        !ui::use_colors(),
+    );
+    ui::style_table(&mut table);
+    println!("\n{}\n", month.format("%B %Y"));
+    println!("{table}");
+}
+
+/// Run full-screen dashboard of the monthly hours
+pub fn run_dashboard() {
+    if !std::io::stdout().is_terminal() {
+        eprintln!("Dashboard needs a terminal");
+        std::process::exit(1);
+    }
     let config = settings::load();
-    let mut time_entries = get_disk_store().load();
+    let month = dates::month_first_and_last_dates(&Local::now().date_naive()).0;
+    let time_entries = exit_on_error(month_entries(&config, &month));
+    let mut dashboard = tui::Dashboard::new(month, time_entries, project_configs(&config));
+
+    let result = tui::run(&mut dashboard, |dashboard, command| match command {
+        tui::Command::Quit => {}
+        tui::Command::Refresh(month) => {
+            match try_refresh_hours().and_then(|_| month_entries(&try_load_settings()?, &month)) {
+                Ok(time_entries) => {
+                    dashboard.set_time_entries(time_entries);
+                    dashboard.set_message("Updated monthly hours from integrations".to_string());
+                }
+                Err(err) => dashboard.set_message(err),
+            }
+        }
+        tui::Command::LoadMonth(month) => {
+            match try_load_settings().and_then(|config| month_entries(&config, &month)) {
+                Ok(time_entries) => dashboard.set_time_entries(time_entries),
+                Err(err) => dashboard.set_message(err),
+            }
+        }
+        tui::Command::SaveTarget(key, period, target) => match save_target(key, period, target) {
+            Ok(project_configs) => dashboard.set_project_configs(project_configs),
+            Err(err) => dashboard.set_message(err),
+        },
+    });
+    if let Err(err) = result {
+        eprintln!("Error occured in dashboard: {}", err);
+    }
+}
+
+/// Save target edited in the dashboard. Returns the updated project configs
+fn save_target(
+    key: ProjectHash,
+    period: tui::Period,
+    target: Option<TargetHours>,
+) -> Result<settings::ProjectConfigs, String> {
+    let mut config = try_load_settings()?;
+    let mut project_configs = project_configs(&config);
+    let project_config = project_configs.configs.entry(key).or_default();
+    match period {
+        tui::Period::Daily => project_config.target_daily_hours = target,
+        tui::Period::Weekly => project_config.target_weekly_hours = target,
+        tui::Period::Monthly => project_config.target_monthly_hours = target,
+    }
+    config.project_configs = Some(project_configs.clone());
+    settings::save(&config).map_err(|err| format!("Error occured during saving: {}", err))?;
+    Ok(project_configs)
+}
+
+/// Entries which aren't marked as non-billable and the number of left out entries
+fn without_non_billable(time_entries: types::TimeEntries) -> (types::TimeEntries, usize) {
+    let (entries, non_billable): (Vec<types::TimeEntry>, Vec<types::TimeEntry>) = time_entries
+        .entries
+        .into_iter()
+        .partition(|entry| entry.billable != Some(false));
+    (types::TimeEntries { entries }, non_billable.len())
+}
+
+/// Settings for the dashboard and status, which mustn't ask anything when loading fails
+fn try_load_settings() -> Result<settings::Config, String> {
+    settings::try_load().map_err(|err| err.to_string())
+}
+
+/// Entries of the month with merged projects combined and ignored projects left out
+fn month_entries(
+    config: &settings::Config,
+    month: &NaiveDate,
+) -> Result<types::TimeEntries, String> {
+    let (from, to) = dates::month_first_and_last_dates(month);
+    try_prepared_entries_between(config, &from, &to)
+}
+
+/// Options for exporting hours
+#[derive(Debug)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Export total hours per project instead of time entries
+    pub summary: bool,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// File to write to, stdout is used if not given
+    pub output: Option<PathBuf>,
+    pub filters: Filters,
+}
+
+/// Export time entries or summary of them
+pub fn export(options: &ExportOptions) {
+    let config = settings::load();
+    let (month_start, month_end) = dates::month_first_and_last_dates(&Local::now().date_naive());
+    let from = options.from.unwrap_or(month_start);
+    let to = options.to.unwrap_or(month_end);
+    if from > to {
+        eprintln!("Start date {} is after end date {}", from, to);
+        std::process::exit(1);
+    }
+
+    let time_entries = options
+        .filters
+        .apply(prepared_entries_between(&config, &from, &to));
+
+    let writer = output_writer(&options.output);
+    let result = if options.summary {
+        export::write_summary(&time_entries, &options.format, writer)
+    } else {
+        export::write_entries(&time_entries, &options.format, writer)
+    };
+    match (result, &options.output) {
+        (Ok(_), Some(path)) => eprintln!("Exported hours to {}", path.display()),
+        (Ok(_), None) => {}
+        (Err(err), _) => eprintln!("Error occured during exporting hours: {}", err),
+    }
+}
+
+/// Options for generating a timesheet
+#[derive(Debug)]
+pub struct TimesheetOptions {
+    pub client: ClientName,
+    /// First day of the month
+    pub month: NaiveDate,
+    /// File to write to, stdout is used if not given
+    pub output: Option<PathBuf>,
+    pub filters: Filters,
+}
+
+/// Render HTML timesheet of client's hours for a month
+pub fn timesheet(options: &TimesheetOptions) {
+    let config = settings::load();
+    let (client, client_entries) =
+        client_entries_for_month(&config, &options.client, &options.month, &options.filters);
+    let project_configs = project_configs(&config);
+    let billable_amount = billing::format_amount(
+        billing::total_amount_cents(
+            &client_entries,
+            &project_configs,
+            &client_configs(&config),
+            default_rounding(&config),
+        ),
+        &config.currency,
+    );
+
+    let html = timesheet::render(
+        &template(TIMESHEET_TEMPLATE_FILENAME, timesheet::DEFAULT_TEMPLATE),
+        &client,
+        &options.month,
+        &client_entries,
+        &billable_amount,
+    );
+    let mut writer = output_writer(&options.output);
+    match (writer.write_all(html.as_bytes()), &options.output) {
+        (Ok(_), Some(path)) => eprintln!("Timesheet written to {}", path.display()),
+        (Ok(_), None) => {}
+        (Err(err), _) => eprintln!("Error occured during writing timesheet: {}", err),
+    }
+}
+
+/// Save the default timesheet template to the settings folder for customizing
+pub fn save_timesheet_template() {
+    save_template(TIMESHEET_TEMPLATE_FILENAME, timesheet::DEFAULT_TEMPLATE)
+}
+
+/// Options for generating an invoice draft
+#[derive(Debug)]
+pub struct InvoiceOptions {
+    pub client: ClientName,
+    /// First day of the month
+    pub month: NaiveDate,
+    /// Folder to write the drafts to, current folder is used if not given
+    pub output_dir: Option<PathBuf>,
+    /// Invoice also entries marked as non-billable
+    pub include_non_billable: bool,
+    pub filters: Filters,
+}
+
+/// Write invoice draft of client's hours for a month as JSON and HTML and increase invoice number
+pub fn invoice(options: &InvoiceOptions) {
+    let config = settings::load();
+    let (client, client_entries) =
+        client_entries_for_month(&config, &options.client, &options.month, &options.filters);
+    let client_entries = if options.include_non_billable {
+        client_entries
+    } else {
+        let (client_entries, left_out) = without_non_billable(client_entries);
+        if left_out > 0 {
+            eprintln!(
+                "Left out {} non-billable entries, use --include-non-billable to invoice them",
+                left_out
+            );
+        }
+        client_entries
+    };
+    let invoice = invoice::generate(
+        &client,
+        &options.month,
+        &client_entries,
+        &project_configs(&config),
+        &client_configs(&config),
+        &config.invoice.clone().unwrap_or_default(),
+        &config.currency,
+    );
+
+    let output_dir = options.output_dir.clone().unwrap_or_default();
+    let file_name: String = format!("invoice-{}", invoice.number)
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let json_path = output_dir.join(format!("{}.json", file_name));
+    let html_path = output_dir.join(format!("{}.html", file_name));
+    for path in [&json_path, &html_path] {
+        if path.exists() {
+            eprintln!("{} already exists", path.display());
+            std::process::exit(1);
+        }
+    }
+    let html = invoice::render(
+        &template(INVOICE_TEMPLATE_FILENAME, invoice::DEFAULT_TEMPLATE),
+        &invoice,
+    );
+    let result = std::fs::write(&json_path, serde_json::to_string_pretty(&invoice).unwrap())
+        .and_then(|_| std::fs::write(&html_path, html));
+    if let Err(err) = result {
+        eprintln!("Error occured during writing invoice: {}", err);
+        std::process::exit(1);
+    }
+    eprintln!(
+        "Invoice {} written to {} and {}",
+        invoice.number,
+        json_path.display(),
+        html_path.display()
+    );
+
+    // Settings are loaded again as refreshing hours may have changed them
+    let mut config = settings::load();
+    let mut invoice_config = config.invoice.clone().unwrap_or_default();
+    invoice_config.next_number = Some(invoice_config.next_number.unwrap_or(1) + 1);
+    config.invoice = Some(invoice_config);
+    if let Err(err) = settings::save(&config) {
+        eprintln!("Error occured during saving next invoice number: {}", err);
+    }
+}
+
+/// Save the default invoice template to the settings folder for customizing
+pub fn save_invoice_template() {
+    save_template(INVOICE_TEMPLATE_FILENAME, invoice::DEFAULT_TEMPLATE)
+}
 
-    if refresh_required(&config) {
-        time_entries = refresh_hours();
+/// Client's entries of the month matching the filters. Exits if the client doesn't have any
+/// hours in the month
+fn client_entries_for_month(
+    config: &settings::Config,
+    client: &ClientName,
+    month: &NaiveDate,
+    filters: &Filters,
+) -> (ClientName, types::TimeEntries) {
+    let (from, to) = dates::month_first_and_last_dates(month);
+    let time_entries = filters.apply(prepared_entries_between(config, &from, &to));
+    let client_entries = types::TimeEntries {
+        entries: time_entries
+            .entries
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .client
+                    .as_ref()
+                    .is_some_and(|name| name.as_str().eq_ignore_ascii_case(client.as_str()))
+            })
+            .collect(),
+    };
+    if client_entries.entries.is_empty() {
+        eprintln!(
+            "No hours found for client {} in {}",
+            client,
+            month.format("%Y-%m")
+        );
+        std::process::exit(1);
+    }
+    let client = client_entries.entries[0].client.clone().unwrap();
+    (client, client_entries)
+}
+
+fn save_template(file_name: &str, default_template: &str) {
+    let path = settings::app_file_path(file_name).unwrap_or_else(|err| {
+        eprintln!("Failed to locate {}: {}", file_name, err);
+        std::process::exit(1);
+    });
+    if std::path::Path::new(&path).exists() {
+        println!("Template already exists: {}", path);
+        return;
+    }
+    match std::fs::write(&path, default_template) {
+        Ok(_) => println!("Template saved: {}", path),
+        Err(err) => println!("Error occured during saving template: {}", err),
     }
+}
 
-    let project_configs = ensure_and_get_projects_configs(config, &time_entries.uniq_projects());
-    let table = table::generate_table(&time_entries, &project_configs);
-    println!("\n{table}");
+/// Template from the settings folder if it exists, otherwise the default template
+fn template(file_name: &str, default_template: &str) -> String {
+    settings::app_file_path(file_name)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_else(|| default_template.to_string())
+}
+
+fn output_writer(output: &Option<PathBuf>) -> Box<dyn std::io::Write> {
+    match output {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(err) => {
+                eprintln!("Couldn't create {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(std::io::stdout()),
+    }
+}
+
+/// Time entries between given dates with merged projects combined and ignored projects left out
+fn prepared_entries_between(
+    config: &settings::Config,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> types::TimeEntries {
+    exit_on_error(try_prepared_entries_between(config, from, to))
+}
+
+fn try_prepared_entries_between(
+    config: &settings::Config,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<types::TimeEntries, String> {
+    let time_entries = try_time_entries_between(config, from, to)?;
+    let project_configs = project_configs(config);
+    let time_entries = with_merged_projects(time_entries, &project_configs);
+    Ok(without_ignored_projects(time_entries, &project_configs))
+}
+
+/// Project configs from settings, empty if there are none yet
+fn project_configs(config: &settings::Config) -> settings::ProjectConfigs {
+    config.project_configs.clone().unwrap_or(settings::ProjectConfigs {
+        configs: HashMap::new(),
+    })
+}
+
+/// Client configs from settings, empty if there are none yet
+fn client_configs(config: &settings::Config) -> settings::ClientConfigs {
+    config.client_configs.clone().unwrap_or(settings::ClientConfigs {
+        configs: HashMap::new(),
+    })
+}
+
+/// Rounding of billed hours for projects without their own rounding
+fn default_rounding(config: &settings::Config) -> Option<Rounding> {
+    config.invoice.as_ref().and_then(|invoice| invoice.rounding)
+}
+
+/// Time entries between given dates. Stored entries are used for the current month and
+/// other months are fetched from the integrations. Running timers are counted until now
+fn time_entries_between(
+    config: &settings::Config,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> types::TimeEntries {
+    exit_on_error(try_time_entries_between(config, from, to))
+}
+
+fn try_time_entries_between(
+    config: &settings::Config,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<types::TimeEntries, String> {
+    let (month_start, month_end) = dates::month_first_and_last_dates(&Local::now().date_naive());
+    let time_entries = if from >= &month_start && to <= &month_end {
+        if refresh_required(config) {
+            try_refresh_hours()?
+        } else {
+            try_stored_time_entries()?
+        }
+    } else {
+        integrations::get_time_entries_for_dates(from, to)?
+    };
+    Ok(types::TimeEntries {
+        entries: time_entries
+            .entries
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .start
+                    .is_some_and(|start| (from..=to).contains(&&start.date_naive()))
+            })
+            .collect(),
+    }
+    .with_running_until(Local::now()))
+}
+
+/// Time entries stored during the latest refresh
+pub fn stored_time_entries() -> types::TimeEntries {
+    match get_disk_store().load() {
+        Ok(time_entries) => time_entries,
+        Err(err) => {
+            settings::recover_from_load_error(&err);
+            types::TimeEntries { entries: vec![] }
+        }
+    }
+}
+
+/// Time entries stored during the latest refresh, without asking anything when they can't be
+/// loaded
+fn try_stored_time_entries() -> Result<types::TimeEntries, String> {
+    get_disk_store().load().map_err(|err| err.to_string())
 }
 
 /// Refresh hours for current month from the integrations
@@ -34,19 +721,36 @@ pub fn refresh_all() {
 
 /// Print some basic info
 pub fn print_info() {
-    println!("Settings folder: {}", settings::settings_path().unwrap())
+    match settings::settings_path() {
+        Ok(path) => println!("Settings folder: {}", path),
+        Err(err) => eprintln!("{}", err),
+    }
 }
 
 fn refresh_hours() -> types::TimeEntries {
-    let time_entries = integrations::get_monthly_time_entries();
-    match get_disk_store().save(&time_entries) {
-        Ok(_) => println!("Updated monthly hours from integrations"),
-        Err(err) => println!("Error occured during refreshing hours: {}", err),
-    }
-    settings::hours_refreshed();
+    let time_entries = exit_on_error(try_refresh_hours());
+    eprintln!("Updated monthly hours from integrations");
     time_entries
 }
 
+/// Fetch current month from the integrations and store the entries
+fn try_refresh_hours() -> Result<types::TimeEntries, String> {
+    let time_entries = integrations::get_monthly_time_entries()?;
+    get_disk_store()
+        .save(&time_entries)
+        .map_err(|err| format!("Error occured during refreshing hours: {}", err))?;
+    settings::hours_refreshed()?;
+    Ok(time_entries)
+}
+
+/// Print the error and exit, used by commands which can't continue without the entries
+fn exit_on_error(result: Result<types::TimeEntries, String>) -> types::TimeEntries {
+    result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    })
+}
+
 fn refresh_required(config: &settings::Config) -> bool {
     let treshold_minutes = match config.refresh_treshold {
         None => return true,
@@ -61,7 +765,7 @@ fn refresh_required(config: &settings::Config) -> bool {
 }
 
 fn ensure_and_get_projects_configs(
-    config: settings::Config,
+    config: &mut settings::Config,
     projects_from_entries: &[types::Project],
 ) -> settings::ProjectConfigs {
     let mut config_changed = false;
@@ -71,57 +775,246 @@ fn ensure_and_get_projects_configs(
             configs: HashMap::new(),
         },
     };
+    if project_configs.migrate_legacy_keys(projects_from_entries) {
+        config_changed = true;
+    }
     for project in projects_from_entries {
-        let project_config = project_configs.get(project);
-        if project_config.is_none() {
-            let target_daily_hours = ask_target(&format!(
-                "What is your target daily hours for {}?",
-                project.title
-            ));
-            let target_weekly_hours = ask_target(&format!(
-                "What is your target weekly hours for {}?",
-                project.title
-            ));
-            let target_monthly_hours = ask_target(&format!(
-                "What is your target monthly hours for {}?",
-                project.title
-            ));
-            let new_config = settings::ProjectConfig {
-                target_daily_hours,
-                target_weekly_hours,
-                target_monthly_hours,
+        if project_configs.get(project).is_some() || !ui::is_interactive() {
+            continue;
+        }
+        let (target_daily_hours, target_weekly_hours, target_monthly_hours) =
+            match ask_targets(&project.title.to_string()) {
+                Ok(targets) => targets,
+                Err(err) => {
+                    eprintln!(
+                        "Couldn't ask targets for {}: {}. Skipping remaining projects",
+                        project.title, err
+                    );
+                    break;
+                }
             };
-            config_changed = true;
-            project_configs
-                .configs
-                .insert(project.key.clone(), new_config);
+        let new_config = settings::ProjectConfig {
+            target_daily_hours,
+            target_weekly_hours,
+            target_monthly_hours,
+            ..Default::default()
         };
+        config_changed = true;
+        project_configs
+            .configs
+            .insert(project.key.clone(), new_config);
     }
     if config_changed {
-        let updated_config = settings::Config {
-            project_configs: Some(project_configs.clone()),
-            ..config
-        };
-        match settings::save(&updated_config) {
-            Ok(_) => println!("New project configs saved"),
-            Err(err) => println!("Error occured during saving new project configs: {}", err),
+        config.project_configs = Some(project_configs.clone());
+        match settings::save(config) {
+            Ok(_) => eprintln!("New project configs saved"),
+            Err(err) => eprintln!("Error occured during saving new project configs: {}", err),
         }
     }
     project_configs
 }
 
-fn ask_target(question: &str) -> Option<u8> {
-    match ui::ask_input::<u8>(question) {
-        Some(num) if num > 0 => Some(num),
-        _ => None,
+fn ensure_and_get_client_configs(
+    config: &mut settings::Config,
+    clients_from_entries: &[types::Client],
+) -> settings::ClientConfigs {
+    let mut config_changed = false;
+    let mut client_configs = match &config.client_configs {
+        Some(client_configs) => client_configs.clone(),
+        None => settings::ClientConfigs {
+            configs: HashMap::new(),
+        },
+    };
+    for client in clients_from_entries {
+        let name = match &client.name {
+            Some(name) => name,
+            None => continue,
+        };
+        if client_configs.get(client).is_some() || !ui::is_interactive() {
+            continue;
+        }
+        let (target_daily_hours, target_weekly_hours, target_monthly_hours) =
+            match ask_targets(&format!("client {}", name)) {
+                Ok(targets) => targets,
+                Err(err) => {
+                    eprintln!(
+                        "Couldn't ask targets for client {}: {}. Skipping remaining clients",
+                        name, err
+                    );
+                    break;
+                }
+            };
+        let new_config = settings::ClientConfig {
+            target_daily_hours,
+            target_weekly_hours,
+            target_monthly_hours,
+            ..Default::default()
+        };
+        config_changed = true;
+        client_configs.configs.insert(name.clone(), new_config);
+    }
+    if config_changed {
+        config.client_configs = Some(client_configs.clone());
+        match settings::save(config) {
+            Ok(_) => eprintln!("New client configs saved"),
+            Err(err) => eprintln!("Error occured during saving new client configs: {}", err),
+        }
+    }
+    client_configs
+}
+
+/// Show entries of merged projects under the project they were merged into
+fn with_merged_projects(
+    time_entries: types::TimeEntries,
+    project_configs: &settings::ProjectConfigs,
+) -> types::TimeEntries {
+    let keys: Vec<ProjectHash> = time_entries
+        .entries
+        .iter()
+        .map(|entry| entry.project_key())
+        .collect();
+    let mut entries_by_key: HashMap<&ProjectHash, &types::TimeEntry> = HashMap::new();
+    for (key, entry) in keys.iter().zip(time_entries.entries.iter()) {
+        entries_by_key.entry(key).or_insert(entry);
+    }
+    let entries = keys
+        .iter()
+        .zip(time_entries.entries.iter())
+        .map(|(key, entry)| {
+            let resolved = project_configs.resolve_key(key);
+            if &resolved == key {
+                return entry.clone();
+            }
+            if let Some(other) = entries_by_key.get(&resolved) {
+                return types::TimeEntry {
+                    project: other.project.clone(),
+                    client: other.client.clone(),
+                    project_source: other.project_source.clone(),
+                    ..entry.clone()
+                };
+            }
+            match project_configs.configs.get(&resolved) {
+                Some(settings::ProjectConfig {
+                    title: Some(title),
+                    client,
+                    project_source,
+                    ..
+                }) => types::TimeEntry {
+                    project: title.clone(),
+                    client: client.clone(),
+                    project_source: project_source.clone(),
+                    ..entry.clone()
+                },
+                _ => entry.clone(),
+            }
+        })
+        .collect();
+    types::TimeEntries { entries }
+}
+
+fn without_ignored_projects(
+    time_entries: types::TimeEntries,
+    project_configs: &settings::ProjectConfigs,
+) -> types::TimeEntries {
+    types::TimeEntries {
+        entries: time_entries
+            .entries
+            .into_iter()
+            .filter(|entry| !project_configs.is_ignored(&entry.project_key()))
+            .collect(),
+    }
+}
+
+type Targets = (Option<TargetHours>, Option<TargetHours>, Option<TargetHours>);
+
+fn ask_targets(name: &str) -> Result<Targets, ui::InputError> {
+    Ok((
+        ask_target(&format!("What is your target daily hours for {}?", name))?,
+        ask_target(&format!("What is your target weekly hours for {}?", name))?,
+        ask_target(&format!("What is your target monthly hours for {}?", name))?,
+    ))
+}
+
+fn ask_target(question: &str) -> Result<Option<TargetHours>, ui::InputError> {
+    loop {
+        match ui::ask_input::<TargetHours>(&format!("{} (e.g. 7h30m or 7.5)", question)) {
+            Ok(Some(target)) if !target.is_zero() => return Ok(Some(target)),
+            Ok(_) => return Ok(None),
+            Err(ui::InputError::Invalid(value)) => {
+                println!("'{}' is not a valid target, try again or leave empty to skip", value)
+            }
+            Err(err) => return Err(err),
+        }
     }
 }
 
 
 fn get_disk_store() -> DiskStore {
-    let filepath_str =  settings::app_path(HOURS_FILENAME)
-    .unwrap_or_else(|| panic!("Failed to locate {}", HOURS_FILENAME));
+    let filepath_str = settings::app_file_path(HOURS_FILENAME).unwrap_or_else(|err| {
+        eprintln!("Failed to locate {}: {}", HOURS_FILENAME, err);
+        std::process::exit(1);
+    });
     DiskStore{
         path: FilePath::new(filepath_str)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hours::types::{Provider, ProjectSource, TimeEntryCalculations};
+    use crate::strict_string::ProjectName;
+
+    fn entry(project: &str, project_id: usize) -> types::TimeEntry {
+        types::TimeEntry {
+            project: ProjectName::new(project),
+            project_source: Some(ProjectSource {
+                provider: Provider::Toggl,
+                workspace_id: 1,
+                project_id,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn shows_merged_entries_under_target_without_own_entries() {
+        let (old, new) = (entry("Old website", 1), entry("Website", 2));
+        let mut project_configs = settings::ProjectConfigs {
+            configs: HashMap::new(),
+        };
+        project_configs.merge(&old.project_key(), &new.project_key());
+        project_configs.remember_project(&types::TimeEntries {
+            entries: vec![new.clone()],
+        }
+        .uniq_projects()[0]);
+
+        let time_entries = with_merged_projects(
+            types::TimeEntries {
+                entries: vec![old.clone(), old],
+            },
+            &project_configs,
+        );
+
+        let projects = time_entries.uniq_projects();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].title, ProjectName::new("Website"));
+        assert_eq!(projects[0].key, new.project_key());
+        assert_eq!(projects[0].entries().len(), 2);
+    }
+
+    #[test]
+    fn leaves_out_only_entries_marked_as_non_billable() {
+        let entry = |billable: Option<bool>| types::TimeEntry {
+            billable,
+            ..entry("Website", 1)
+        };
+        let time_entries = types::TimeEntries {
+            entries: vec![entry(Some(true)), entry(Some(false)), entry(None)],
+        };
+
+        let (time_entries, left_out) = without_non_billable(time_entries);
+
+        assert_eq!(time_entries.entries, vec![entry(Some(true)), entry(None)]);
+        assert_eq!(left_out, 1);
+    }
+}
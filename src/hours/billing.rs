@@ -0,0 +1,194 @@
+//! Billable amounts of tracked hours
+use crate::hours::rounding::Rounding;
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::settings::{ClientConfigs, ProjectConfigs};
+use chrono::Duration;
+
+/// Hourly rate for project. Project's own rate is preferred over the client's rate
+pub fn hourly_rate(
+    project: &types::Project,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+) -> Option<f64> {
+    project_configs
+        .get(project)
+        .and_then(|config| config.hourly_rate)
+        .or_else(|| {
+            project
+                .client
+                .as_ref()
+                .and_then(|client| client_configs.configs.get(client))
+                .and_then(|config| config.hourly_rate)
+        })
+}
+
+/// Billable amount of project in cents. Amounts from the integration are used when available,
+/// otherwise the billed hours are multiplied with the hourly rate
+pub fn project_amount_cents(
+    project: &types::Project,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+    default_rounding: Option<Rounding>,
+) -> i64 {
+    project.billable_amount_cents(
+        hourly_rate(project, project_configs, client_configs),
+        project_rounding(project, project_configs, default_rounding).as_ref(),
+    )
+}
+
+/// Rounding rules of project. Project's own rounding is preferred over the default rounding
+pub fn project_rounding(
+    project: &types::Project,
+    project_configs: &ProjectConfigs,
+    default_rounding: Option<Rounding>,
+) -> Option<Rounding> {
+    project_configs
+        .get(project)
+        .and_then(|config| config.rounding)
+        .or(default_rounding)
+}
+
+/// Tracked hours of project rounded with the project's rounding rules
+pub fn billed_hours(
+    project: &types::Project,
+    project_configs: &ProjectConfigs,
+    default_rounding: Option<Rounding>,
+) -> Duration {
+    match project_rounding(project, project_configs, default_rounding) {
+        Some(rounding) => project.rounded_hours(&rounding),
+        None => project.total_hours(),
+    }
+}
+
+/// Billable amount of all entries in cents
+pub fn total_amount_cents(
+    time_entries: &types::TimeEntries,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+    default_rounding: Option<Rounding>,
+) -> i64 {
+    time_entries
+        .uniq_projects()
+        .iter()
+        .map(|project| {
+            project_amount_cents(project, project_configs, client_configs, default_rounding)
+        })
+        .sum()
+}
+
+/// Returns true if there's anything billable, i.e. amounts from the integration or hourly rates
+pub fn is_billing_used(
+    time_entries: &types::TimeEntries,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+) -> bool {
+    time_entries
+        .entries
+        .iter()
+        .any(|entry| entry.billable_amount_cents > 0)
+        || project_configs
+            .configs
+            .values()
+            .any(|config| config.hourly_rate.is_some())
+        || client_configs
+            .configs
+            .values()
+            .any(|config| config.hourly_rate.is_some())
+}
+
+/// Format amount in cents with optional currency, e.g. `1234.50 EUR`
+pub fn format_amount(amount_cents: i64, currency: &Option<String>) -> String {
+    let amount = format!("{:.2}", amount_cents as f64 / 100.0);
+    match currency {
+        Some(currency) if !currency.is_empty() => format!("{} {}", amount, currency),
+        _ => amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{ClientConfig, ProjectConfig};
+    use crate::strict_string::{ClientName, ProjectName};
+    use chrono::Local;
+    use std::collections::HashMap;
+
+    fn project(client: &str, title: &str, amount_cents: usize) -> types::Project {
+        let start = Local::now();
+        let entry = types::TimeEntry {
+            client: Some(ClientName::new(client)),
+            project: ProjectName::new(title),
+            start: Some(start),
+            end: Some(start + Duration::minutes(90)),
+            billable_amount_cents: amount_cents,
+            ..Default::default()
+        };
+        types::Project {
+            client: entry.client.clone(),
+            title: entry.project.clone(),
+            key: entry.project_key(),
+            entries: vec![entry],
+        }
+    }
+
+    #[test]
+    fn uses_project_rate_then_client_rate() {
+        let website = project("Acme", "Website", 0);
+        let backend = project("Acme", "Backend", 0);
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([(
+                website.key.clone(),
+                ProjectConfig {
+                    hourly_rate: Some(100.0),
+                    ..Default::default()
+                },
+            )]),
+        };
+        let client_configs = ClientConfigs {
+            configs: HashMap::from([(
+                ClientName::new("Acme"),
+                ClientConfig {
+                    hourly_rate: Some(80.0),
+                    ..Default::default()
+                },
+            )]),
+        };
+
+        assert_eq!(
+            project_amount_cents(&website, &project_configs, &client_configs, None),
+            15000
+        );
+        assert_eq!(
+            project_amount_cents(&backend, &project_configs, &client_configs, None),
+            12000
+        );
+    }
+
+    #[test]
+    fn prefers_amounts_from_integration() {
+        let website = project("Acme", "Website", 9900);
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([(
+                website.key.clone(),
+                ProjectConfig {
+                    hourly_rate: Some(100.0),
+                    ..Default::default()
+                },
+            )]),
+        };
+        let client_configs = ClientConfigs {
+            configs: HashMap::new(),
+        };
+
+        assert_eq!(
+            project_amount_cents(&website, &project_configs, &client_configs, None),
+            9900
+        );
+    }
+
+    #[test]
+    fn formats_amount() {
+        assert_eq!(format_amount(123450, &Some("EUR".to_string())), "1234.50 EUR");
+        assert_eq!(format_amount(5, &None), "0.05");
+    }
+}
@@ -0,0 +1,274 @@
+//! Month calendar of daily hours
+use crate::hours::table::format_short_duration;
+use crate::hours::target::{TargetHours, TargetStatus};
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::settings::ProjectConfigs;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use comfy_table::{presets::ASCII_NO_BORDERS, Attribute, Cell, CellAlignment, Color, Table};
+
+/// Generate calendar of the month with total hours of each day. Past days and today are colored
+/// against the daily target, or marked with status symbols, weekends and holidays are dimmed
+pub fn generate_calendar(
+    month: &NaiveDate,
+    time_entries: &types::TimeEntries,
+    daily_target: Option<TargetHours>,
+    holidays: &[NaiveDate],
+    today: &NaiveDate,
+    status_symbols: bool,
+) -> Table {
+    let mut table = Table::new();
+    table.load_preset(ASCII_NO_BORDERS).set_header(
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+            .iter()
+            .map(|day| {
+                Cell::new(day)
+                    .add_attribute(Attribute::Bold)
+                    .set_alignment(CellAlignment::Right)
+            }),
+    );
+
+    let first = month.with_day(1).unwrap();
+    let mut row: Vec<Cell> = (0..first.weekday().num_days_from_monday())
+        .map(|_| Cell::new(""))
+        .collect();
+    for date in first.iter_days().take_while(|date| date.month() == first.month()) {
+        let hours = daily_hours(time_entries, &date);
+        let is_day_off = is_weekend(&date) || holidays.contains(&date);
+        let status = day_status(&date, &hours, daily_target, is_day_off, today);
+        let symbol = if status_symbols { status.symbol() } else { "" };
+        row.push(day_cell(&date, &hours, symbol, today).fg(day_color(status, is_day_off)));
+        if date.weekday() == Weekday::Sun {
+            table.add_row(std::mem::take(&mut row));
+        }
+    }
+    if !row.is_empty() {
+        table.add_row(row);
+    }
+    table
+}
+
+/// Daily target from the settings, or sum of daily targets of the visible projects
+pub fn daily_target(
+    target_daily_hours: Option<TargetHours>,
+    project_configs: &ProjectConfigs,
+) -> Option<TargetHours> {
+    target_daily_hours.or_else(|| {
+        let minutes: i64 = project_configs
+            .configs
+            .values()
+            .filter(|config| !config.is_hidden() && config.merged_into.is_none())
+            .filter_map(|config| config.target_daily_hours)
+            .map(|target| target.num_minutes())
+            .sum();
+        if minutes > 0 {
+            Some(TargetHours::from_minutes(minutes as u32))
+        } else {
+            None
+        }
+    })
+}
+
+fn daily_hours(time_entries: &types::TimeEntries, date: &NaiveDate) -> Duration {
+    types::TimeEntries {
+        entries: time_entries
+            .entries
+            .iter()
+            .filter(|entry| entry.start.is_some_and(|start| &start.date_naive() == date))
+            .cloned()
+            .collect(),
+    }
+    .total_hours()
+}
+
+fn day_cell(date: &NaiveDate, hours: &Duration, symbol: &str, today: &NaiveDate) -> Cell {
+    let hours = match (format_hours(hours), symbol) {
+        (hours, "") => hours,
+        (hours, symbol) if hours.is_empty() => symbol.to_string(),
+        (hours, symbol) => format!("{} {}", hours, symbol),
+    };
+    let cell =
+        Cell::new(format!("{:>2}\n{:>6}", date.day(), hours)).set_alignment(CellAlignment::Right);
+    if date == today {
+        cell.add_attribute(Attribute::Bold)
+    } else {
+        cell
+    }
+}
+
+/// Status of past days and today against the daily target. Unset for days off and future days
+fn day_status(
+    date: &NaiveDate,
+    hours: &Duration,
+    daily_target: Option<TargetHours>,
+    is_day_off: bool,
+    today: &NaiveDate,
+) -> TargetStatus {
+    if is_day_off || date > today {
+        return TargetStatus::Unset;
+    }
+    TargetStatus::new(&daily_target, hours)
+}
+
+fn day_color(status: TargetStatus, is_day_off: bool) -> Color {
+    if is_day_off {
+        return Color::DarkGrey;
+    }
+    match status {
+        TargetStatus::Met => Color::Green,
+        TargetStatus::Close => Color::Yellow,
+        TargetStatus::Behind => Color::Red,
+        TargetStatus::Unset => Color::Reset,
+    }
+}
+
+fn is_weekend(date: &NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Format hours shortly, e.g. `7h30m`. Empty when nothing is tracked
+fn format_hours(duration: &Duration) -> String {
+    if duration.num_minutes() == 0 {
+        return "".to_string();
+    }
+    format_short_duration(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ProjectConfig;
+    use crate::strict_string::{ProjectHash, ProjectName};
+    use chrono::{Local, TimeZone};
+    use std::collections::HashMap;
+
+    #[test]
+    fn renders_month_grid_starting_from_monday() {
+        let entry = |day: u32, minutes: i64| {
+            let start = Local.with_ymd_and_hms(2022, 2, day, 9, 0, 0).unwrap();
+            types::TimeEntry {
+                project: ProjectName::new("Website"),
+                start: Some(start),
+                end: Some(start + Duration::minutes(minutes)),
+                ..Default::default()
+            }
+        };
+        let time_entries = types::TimeEntries {
+            entries: vec![entry(1, 480), entry(2, 450), entry(2, 15), entry(5, 60)],
+        };
+        let month = NaiveDate::from_ymd_opt(2022, 2, 1).unwrap();
+
+        let mut table = generate_calendar(
+            &month,
+            &time_entries,
+            Some(TargetHours::from_hours(8)),
+            &[],
+            &NaiveDate::from_ymd_opt(2022, 2, 3).unwrap(),
+            false,
+        );
+        table.force_no_tty();
+        let expected = "
+    Mon |    Tue |    Wed |    Thu |    Fri |    Sat |    Sun 
+==============================================================
+        |      1 |      2 |      3 |      4 |      5 |      6 
+        |     8h |  7h45m |        |        |     1h |        
+--------+--------+--------+--------+--------+--------+--------
+      7 |      8 |      9 |     10 |     11 |     12 |     13 
+        |        |        |        |        |        |        
+--------+--------+--------+--------+--------+--------+--------
+     14 |     15 |     16 |     17 |     18 |     19 |     20 
+        |        |        |        |        |        |        
+--------+--------+--------+--------+--------+--------+--------
+     21 |     22 |     23 |     24 |     25 |     26 |     27 
+        |        |        |        |        |        |        
+--------+--------+--------+--------+--------+--------+--------
+     28 |        |        |        |        |        |        
+        |        |        |        |        |        |        ";
+
+        assert_eq!("\n".to_string() + &table.to_string(), expected)
+    }
+
+    #[test]
+    fn marks_days_with_status_symbols() {
+        let start = Local.with_ymd_and_hms(2022, 2, 1, 9, 0, 0).unwrap();
+        let time_entries = types::TimeEntries {
+            entries: vec![types::TimeEntry {
+                project: ProjectName::new("Website"),
+                start: Some(start),
+                end: Some(start + Duration::minutes(450)),
+                ..Default::default()
+            }],
+        };
+
+        let mut table = generate_calendar(
+            &NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+            &time_entries,
+            Some(TargetHours::from_hours(7)),
+            &[],
+            &NaiveDate::from_ymd_opt(2022, 2, 2).unwrap(),
+            true,
+        );
+        table.force_no_tty();
+        let expected = "
+    Mon |     Tue |    Wed |    Thu |    Fri |    Sat |    Sun 
+===============================================================
+        |       1 |      2 |      3 |      4 |      5 |      6 
+        | 7h30m ✓ |      ✗ |        |        |        |        ";
+
+        let first_week: Vec<String> = table.lines().take(4).collect();
+        assert_eq!("\n".to_string() + &first_week.join("\n"), expected)
+    }
+
+    #[test]
+    fn colors_days_against_target() {
+        let today = NaiveDate::from_ymd_opt(2022, 2, 9).unwrap();
+        let target = Some(TargetHours::from_hours(8));
+        let color = |day: u32, minutes: i64, is_day_off: bool| {
+            let date = NaiveDate::from_ymd_opt(2022, 2, day).unwrap();
+            let hours = Duration::minutes(minutes);
+            day_color(
+                day_status(&date, &hours, target, is_day_off, &today),
+                is_day_off,
+            )
+        };
+
+        assert_eq!(color(7, 480, false), Color::Green);
+        assert_eq!(color(8, 120, false), Color::Red);
+        assert_eq!(color(9, 450, false), Color::Yellow);
+        assert_eq!(color(10, 0, false), Color::Reset);
+        assert_eq!(color(6, 0, true), Color::DarkGrey);
+    }
+
+    #[test]
+    fn sums_daily_targets_of_visible_projects() {
+        let config = |target: u32, archived: bool| ProjectConfig {
+            target_daily_hours: Some(TargetHours::from_hours(target)),
+            archived,
+            ..Default::default()
+        };
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([
+                (ProjectHash::new("a"), config(4, false)),
+                (ProjectHash::new("b"), config(3, false)),
+                (ProjectHash::new("c"), config(2, true)),
+            ]),
+        };
+
+        assert_eq!(
+            daily_target(None, &project_configs),
+            Some(TargetHours::from_hours(7))
+        );
+        assert_eq!(
+            daily_target(Some(TargetHours::from_hours(6)), &project_configs),
+            Some(TargetHours::from_hours(6))
+        );
+        assert_eq!(
+            daily_target(
+                None,
+                &ProjectConfigs {
+                    configs: HashMap::new()
+                }
+            ),
+            None
+        );
+    }
+}
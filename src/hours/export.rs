@@ -0,0 +1,245 @@
+//! Export time entries and summaries as CSV or Markdown
+use crate::hours::types::{self, TimeEntryCalculations};
+use chrono::Duration;
+
+/// File format of the export
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma separated values
+    Csv,
+    /// Markdown table
+    Markdown,
+}
+
+/// Write time entries, one row per entry
+pub fn write_entries(
+    time_entries: &types::TimeEntries,
+    format: &ExportFormat,
+    writer: impl std::io::Write,
+) -> std::io::Result<()> {
+    let header = [
+        "Date",
+        "Start",
+        "End",
+        "Client",
+        "Project",
+        "Description",
+        "Hours",
+        "Duration",
+        "Billable",
+        "Tags",
+        "Task",
+    ];
+    let mut entries: Vec<&types::TimeEntry> = time_entries.entries.iter().collect();
+    entries.sort_by_key(|entry| entry.start);
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            vec![
+                format_datetime(entry.start, "%Y-%m-%d"),
+                format_datetime(entry.start, "%H:%M"),
+                format_datetime(entry.end, "%H:%M"),
+                entry
+                    .client
+                    .as_ref()
+                    .map(|client| client.to_string())
+                    .unwrap_or_default(),
+                entry.project.to_string(),
+                entry.description.to_string(),
+                format_decimal_hours(&entry.duration()),
+                format_hours_minutes(&entry.duration()),
+                if entry.billable == Some(true) { "yes" } else { "no" }.to_string(),
+                entry.tags.join(", "),
+                entry.task.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    write_rows(format, &header, &rows, writer)
+}
+
+/// Write total hours per project with a total row
+pub fn write_summary(
+    time_entries: &types::TimeEntries,
+    format: &ExportFormat,
+    writer: impl std::io::Write,
+) -> std::io::Result<()> {
+    let header = [
+        "Client",
+        "Project",
+        "Hours",
+        "Duration",
+        "Billable hours",
+        "Non-billable hours",
+    ];
+    let mut rows: Vec<Vec<String>> = time_entries
+        .uniq_projects()
+        .iter()
+        .map(|project| {
+            vec![
+                project
+                    .client
+                    .as_ref()
+                    .map(|client| client.to_string())
+                    .unwrap_or_default(),
+                project.title.to_string(),
+                format_decimal_hours(&project.total_hours()),
+                format_hours_minutes(&project.total_hours()),
+                format_decimal_hours(&project.billable_hours()),
+                format_decimal_hours(&project.non_billable_hours()),
+            ]
+        })
+        .collect();
+    rows.push(vec![
+        "Total".to_string(),
+        "".to_string(),
+        format_decimal_hours(&time_entries.total_hours()),
+        format_hours_minutes(&time_entries.total_hours()),
+        format_decimal_hours(&time_entries.billable_hours()),
+        format_decimal_hours(&time_entries.non_billable_hours()),
+    ]);
+    write_rows(format, &header, &rows, writer)
+}
+
+fn write_rows(
+    format: &ExportFormat,
+    header: &[&str],
+    rows: &[Vec<String>],
+    mut writer: impl std::io::Write,
+) -> std::io::Result<()> {
+    let header: Vec<String> = header.iter().map(|column| column.to_string()).collect();
+    match format {
+        ExportFormat::Csv => {
+            writeln!(writer, "{}", csv_row(&header))?;
+            for row in rows {
+                writeln!(writer, "{}", csv_row(row))?;
+            }
+        }
+        ExportFormat::Markdown => {
+            writeln!(writer, "{}", markdown_row(&header))?;
+            writeln!(writer, "|{}", " --- |".repeat(header.len()))?;
+            for row in rows {
+                writeln!(writer, "{}", markdown_row(row))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn csv_row(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn markdown_row(values: &[String]) -> String {
+    let cells: Vec<String> = values
+        .iter()
+        .map(|value| value.replace('|', "\\|").replace(['\n', '\r'], " "))
+        .collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+fn format_datetime(datetime: Option<chrono::DateTime<chrono::Local>>, format: &str) -> String {
+    datetime
+        .map(|datetime| datetime.format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// Format duration as decimal hours, e.g. `1.50`
+pub fn format_decimal_hours(duration: &Duration) -> String {
+    format!("{:.2}", duration.num_minutes() as f64 / 60.0)
+}
+
+/// Format duration as hours and minutes, e.g. `1:30`
+pub fn format_hours_minutes(duration: &Duration) -> String {
+    let minutes = duration.num_minutes();
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strict_string::{ClientName, Description, ProjectName};
+    use chrono::{Local, TimeZone};
+
+    fn time_entries() -> types::TimeEntries {
+        let entry = |client: Option<&str>, project: &str, description: &str, hour: u32, minutes: i64| {
+            let start = Local.with_ymd_and_hms(2022, 1, 12, hour, 0, 0).unwrap();
+            types::TimeEntry {
+                description: Description::new(description),
+                client: client.map(ClientName::new),
+                project: ProjectName::new(project),
+                start: Some(start),
+                end: Some(start + Duration::minutes(minutes)),
+                billable: Some(client.is_some()),
+                tags: client
+                    .map(|_| vec!["dev".to_string(), "web".to_string()])
+                    .unwrap_or_default(),
+                task: client.map(|_| "Login".to_string()),
+                ..Default::default()
+            }
+        };
+        types::TimeEntries {
+            entries: vec![
+                entry(Some("Acme"), "Website", "Fix \"login\", again", 13, 45),
+                entry(None, "Internal", "Planning | review", 9, 90),
+            ],
+        }
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_decimal_hours(&Duration::minutes(90)), "1.50");
+        assert_eq!(format_decimal_hours(&Duration::minutes(20)), "0.33");
+        assert_eq!(format_hours_minutes(&Duration::minutes(90)), "1:30");
+        assert_eq!(format_hours_minutes(&Duration::minutes(605)), "10:05");
+    }
+
+    #[test]
+    fn exports_entries_as_csv() {
+        let mut result = Vec::new();
+
+        write_entries(&time_entries(), &ExportFormat::Csv, &mut result).unwrap();
+
+        assert_eq!(
+            String::from_utf8(result).unwrap(),
+            "Date,Start,End,Client,Project,Description,Hours,Duration,Billable,Tags,Task
+2022-01-12,09:00,10:30,,Internal,Planning | review,1.50,1:30,no,,
+2022-01-12,13:00,13:45,Acme,Website,\"Fix \"\"login\"\", again\",0.75,0:45,yes,\"dev, web\",Login
+"
+        );
+    }
+
+    #[test]
+    fn exports_summary_as_markdown() {
+        let mut result = Vec::new();
+
+        write_summary(&time_entries(), &ExportFormat::Markdown, &mut result).unwrap();
+
+        assert_eq!(
+            String::from_utf8(result).unwrap(),
+            "| Client | Project | Hours | Duration | Billable hours | Non-billable hours |
+| --- | --- | --- | --- | --- | --- |
+|  | Internal | 1.50 | 1:30 | 0.00 | 1.50 |
+| Acme | Website | 0.75 | 0:45 | 0.75 | 0.00 |
+| Total |  | 2.25 | 2:15 | 0.75 | 1.50 |
+"
+        );
+    }
+
+    #[test]
+    fn escapes_markdown_cells() {
+        assert_eq!(
+            markdown_row(&["a|b".to_string(), "c\nd".to_string()]),
+            "| a\\|b | c d |"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Filter time entries by tags, project, client and description
+use crate::hours::types;
+
+/// Filters for time entries. Entries have to match all of the given filters
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct Filters {
+    /// Only entries with the tag. Can be given multiple times to require all of the tags
+    #[clap(long = "tag")]
+    pub tags: Vec<String>,
+    /// Only entries of the project
+    #[clap(long)]
+    pub project: Option<String>,
+    /// Only entries of the client
+    #[clap(long)]
+    pub client: Option<String>,
+    /// Only entries whose description contains the text
+    #[clap(long)]
+    pub description_match: Option<String>,
+}
+
+/// Filters for reports of a single client, which is given separately
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct ClientFilters {
+    /// Only entries with the tag. Can be given multiple times to require all of the tags
+    #[clap(long = "tag")]
+    pub tags: Vec<String>,
+    /// Only entries of the project
+    #[clap(long)]
+    pub project: Option<String>,
+    /// Only entries whose description contains the text
+    #[clap(long)]
+    pub description_match: Option<String>,
+}
+
+impl From<ClientFilters> for Filters {
+    fn from(filters: ClientFilters) -> Self {
+        Filters {
+            tags: filters.tags,
+            project: filters.project,
+            client: None,
+            description_match: filters.description_match,
+        }
+    }
+}
+
+impl Filters {
+    /// True if no filters are given
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.project.is_none()
+            && self.client.is_none()
+            && self.description_match.is_none()
+    }
+
+    /// Entries matching the filters
+    pub fn apply(&self, time_entries: types::TimeEntries) -> types::TimeEntries {
+        if self.is_empty() {
+            return time_entries;
+        }
+        types::TimeEntries {
+            entries: time_entries
+                .entries
+                .into_iter()
+                .filter(|entry| self.matches(entry))
+                .collect(),
+        }
+    }
+
+    /// Matching is case-insensitive. Tags, project and client have to match exactly
+    fn matches(&self, entry: &types::TimeEntry) -> bool {
+        let tags_match = self.tags.iter().all(|tag| {
+            entry
+                .tags
+                .iter()
+                .any(|entry_tag| entry_tag.to_lowercase() == tag.to_lowercase())
+        });
+        let project_match = self
+            .project
+            .as_ref()
+            .is_none_or(|project| entry.project.as_str().to_lowercase() == project.to_lowercase());
+        let client_match = self.client.as_ref().is_none_or(|client| {
+            entry
+                .client
+                .as_ref()
+                .is_some_and(|name| name.as_str().to_lowercase() == client.to_lowercase())
+        });
+        let description_match = self.description_match.as_ref().is_none_or(|text| {
+            entry
+                .description
+                .as_str()
+                .to_lowercase()
+                .contains(&text.to_lowercase())
+        });
+        tags_match && project_match && client_match && description_match
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strict_string::{ClientName, Description, ProjectName};
+
+    fn time_entries() -> types::TimeEntries {
+        let entry = |client: Option<&str>, project: &str, description: &str, tags: &[&str]| {
+            types::TimeEntry {
+                description: Description::new(description),
+                client: client.map(ClientName::new),
+                project: ProjectName::new(project),
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                ..Default::default()
+            }
+        };
+        types::TimeEntries {
+            entries: vec![
+                entry(Some("Acme"), "Website", "Fix login", &["bug", "urgent"]),
+                entry(Some("Acme"), "Website", "Planning", &["meeting"]),
+                entry(Some("Beta"), "Backend", "Fix deploy", &["Bug"]),
+                entry(None, "Internal", "Weekly planning", &[]),
+            ],
+        }
+    }
+
+    fn descriptions(time_entries: &types::TimeEntries) -> Vec<&str> {
+        time_entries
+            .entries
+            .iter()
+            .map(|entry| entry.description.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn no_filters_keep_all_entries() {
+        assert_eq!(Filters::default().apply(time_entries()).entries.len(), 4);
+    }
+
+    #[test]
+    fn filters_by_tags() {
+        let filters = Filters {
+            tags: vec!["bug".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            descriptions(&filters.apply(time_entries())),
+            vec!["Fix login", "Fix deploy"]
+        );
+
+        let filters = Filters {
+            tags: vec!["bug".to_string(), "urgent".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            descriptions(&filters.apply(time_entries())),
+            vec!["Fix login"]
+        );
+    }
+
+    #[test]
+    fn filters_by_project_client_and_description() {
+        let filters = Filters {
+            client: Some("acme".to_string()),
+            description_match: Some("PLAN".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            descriptions(&filters.apply(time_entries())),
+            vec!["Planning"]
+        );
+
+        let filters = Filters {
+            project: Some("internal".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            descriptions(&filters.apply(time_entries())),
+            vec!["Weekly planning"]
+        );
+    }
+}
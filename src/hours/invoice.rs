@@ -0,0 +1,269 @@
+//! Invoice drafts of client's monthly hours
+//!
+//! Template placeholders: `{{number}}`, `{{date}}`, `{{client}}`, `{{month}}`, `{{lines}}`,
+//! `{{subtotal}}`, `{{vat_percent}}`, `{{vat}}` and `{{total}}`.
+use crate::hours::billing;
+use crate::hours::export::format_decimal_hours;
+use crate::hours::timesheet::{escape, fill_template};
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::settings::{ClientConfigs, InvoiceConfig, ProjectConfigs};
+use crate::strict_string::{ClientName, ProjectName};
+use chrono::{Duration, Local, NaiveDate};
+use serde::Serialize;
+
+/// Template used when there is no template in the settings folder
+pub static DEFAULT_TEMPLATE: &str = include_str!("invoice.html");
+
+/// Invoice draft, amounts are in cents
+#[derive(Debug, Serialize)]
+pub struct Invoice {
+    pub number: String,
+    pub date: NaiveDate,
+    pub client: ClientName,
+    /// Invoiced month as `YYYY-MM`
+    pub month: String,
+    pub currency: Option<String>,
+    pub lines: Vec<InvoiceLine>,
+    pub subtotal_cents: i64,
+    pub vat_percent: f64,
+    pub vat_cents: i64,
+    pub total_cents: i64,
+}
+
+/// Invoiced hours of single project
+#[derive(Debug, Serialize)]
+pub struct InvoiceLine {
+    pub project: ProjectName,
+    pub tracked_minutes: i64,
+    /// Tracked minutes after rounding
+    pub invoiced_minutes: i64,
+    /// `null` when the project has no rate
+    pub hourly_rate: Option<f64>,
+    /// Amounts from the integration and the invoiced hours of other entries times the hourly rate
+    pub amount_cents: i64,
+}
+
+/// Generate invoice of client's entries
+pub fn generate(
+    client: &ClientName,
+    month: &NaiveDate,
+    time_entries: &types::TimeEntries,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+    invoice_config: &InvoiceConfig,
+    currency: &Option<String>,
+) -> Invoice {
+    let lines: Vec<InvoiceLine> = time_entries
+        .uniq_projects()
+        .iter()
+        .map(|project| {
+            let rounding = invoice_config.rounding;
+            InvoiceLine {
+                project: project.title.clone(),
+                tracked_minutes: project.total_hours().num_minutes(),
+                invoiced_minutes: billing::billed_hours(project, project_configs, rounding)
+                    .num_minutes(),
+                hourly_rate: billing::hourly_rate(project, project_configs, client_configs),
+                amount_cents: billing::project_amount_cents(
+                    project,
+                    project_configs,
+                    client_configs,
+                    rounding,
+                ),
+            }
+        })
+        .collect();
+
+    let vat_percent = client_configs
+        .configs
+        .get(client)
+        .and_then(|config| config.vat_percent)
+        .or(invoice_config.vat_percent)
+        .unwrap_or(0.0);
+    let subtotal_cents: i64 = lines.iter().map(|line| line.amount_cents).sum();
+    let vat_cents = (subtotal_cents as f64 * vat_percent / 100.0).round() as i64;
+
+    Invoice {
+        number: invoice_config.next_invoice_number(),
+        date: Local::now().date_naive(),
+        client: client.clone(),
+        month: month.format("%Y-%m").to_string(),
+        currency: currency.clone(),
+        lines,
+        subtotal_cents,
+        vat_percent,
+        vat_cents,
+        total_cents: subtotal_cents + vat_cents,
+    }
+}
+
+/// Render invoice with the template
+pub fn render(template: &str, invoice: &Invoice) -> String {
+    let amount = |cents: i64| escape(&billing::format_amount(cents, &invoice.currency));
+    fill_template(
+        template,
+        &[
+            ("number", escape(&invoice.number)),
+            ("date", invoice.date.format("%Y-%m-%d").to_string()),
+            ("client", escape(invoice.client.as_str())),
+            ("month", invoice.month.clone()),
+            ("lines", line_rows(invoice)),
+            ("subtotal", amount(invoice.subtotal_cents)),
+            ("vat_percent", invoice.vat_percent.to_string()),
+            ("vat", amount(invoice.vat_cents)),
+            ("total", amount(invoice.total_cents)),
+        ],
+    )
+}
+
+fn line_rows(invoice: &Invoice) -> String {
+    invoice
+        .lines
+        .iter()
+        .map(|line| {
+            format!(
+                "    <tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+                escape(line.project.as_str()),
+                format_decimal_hours(&Duration::minutes(line.invoiced_minutes)),
+                line.hourly_rate
+                    .map(|rate| format!("{:.2}", rate))
+                    .unwrap_or_default(),
+                escape(&billing::format_amount(line.amount_cents, &invoice.currency))
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hours::rounding::{Rounding, RoundingDirection, RoundingScope};
+    use crate::settings::{self, ClientConfig, ProjectConfig};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn entry(project: &str, minutes: i64, billable: bool, amount_cents: usize) -> types::TimeEntry {
+        let start = Local.with_ymd_and_hms(2022, 1, 12, 9, 0, 0).unwrap();
+        types::TimeEntry {
+            client: Some(ClientName::new("Acme")),
+            project: ProjectName::new(project),
+            start: Some(start),
+            end: Some(start + Duration::minutes(minutes)),
+            billable: Some(billable),
+            billable_amount_cents: amount_cents,
+            ..Default::default()
+        }
+    }
+
+    fn config() -> settings::Config {
+        settings::Config {
+            currency: Some("EUR".to_string()),
+            project_configs: Some(ProjectConfigs {
+                configs: HashMap::from([(
+                    entry("Website", 0, true, 0).project_key(),
+                    ProjectConfig {
+                        hourly_rate: Some(100.0),
+                        ..Default::default()
+                    },
+                )]),
+            }),
+            client_configs: Some(ClientConfigs {
+                configs: HashMap::from([(
+                    ClientName::new("Acme"),
+                    ClientConfig {
+                        vat_percent: Some(10.0),
+                        ..Default::default()
+                    },
+                )]),
+            }),
+            invoice: Some(InvoiceConfig {
+                next_number: Some(7),
+                number_prefix: Some("2022-".to_string()),
+                vat_percent: Some(24.0),
+                rounding: Some(Rounding {
+                    increment_minutes: 15,
+                    direction: RoundingDirection::Up,
+                    per: RoundingScope::Total,
+                }),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn generate_invoice(time_entries: &types::TimeEntries) -> Invoice {
+        let config = config();
+        generate(
+            &ClientName::new("Acme"),
+            &NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            time_entries,
+            &config.project_configs.unwrap(),
+            &config.client_configs.unwrap(),
+            &config.invoice.unwrap(),
+            &config.currency,
+        )
+    }
+
+    #[test]
+    fn invoices_projects_with_rates_rounding_and_vat() {
+        let time_entries = types::TimeEntries {
+            entries: vec![
+                entry("Website", 50, true, 0),
+                entry("Website", 30, false, 3000),
+                entry("Backend", 60, true, 12000),
+            ],
+        };
+
+        let invoice = generate_invoice(&time_entries);
+
+        assert_eq!(invoice.number, "2022-7");
+        assert_eq!(invoice.month, "2022-01");
+        assert_eq!(invoice.lines.len(), 2);
+        let backend = &invoice.lines[0];
+        assert_eq!(backend.project, ProjectName::new("Backend"));
+        assert_eq!(backend.hourly_rate, None);
+        assert_eq!(backend.amount_cents, 12000);
+        let website = &invoice.lines[1];
+        assert_eq!(website.tracked_minutes, 80);
+        assert_eq!(website.invoiced_minutes, 90);
+        // 30 EUR from the integration and 50 minutes rounded up to an hour with the rate
+        assert_eq!(website.amount_cents, 13000);
+        assert_eq!(invoice.subtotal_cents, 25000);
+        assert_eq!(invoice.vat_percent, 10.0);
+        assert_eq!(invoice.vat_cents, 2500);
+        assert_eq!(invoice.total_cents, 27500);
+    }
+
+    #[test]
+    fn renders_lines_and_totals() {
+        let time_entries = types::TimeEntries {
+            entries: vec![entry("Website <new>", 90, false, 15000)],
+        };
+        let invoice = generate_invoice(&time_entries);
+        let template = "{{number}} {{client}} {{month}}\n{{lines}}\n{{subtotal}} {{vat_percent}} {{vat}} {{total}}";
+
+        assert_eq!(
+            render(template, &invoice),
+            "2022-7 Acme 2022-01
+    <tr><td>Website &lt;new&gt;</td><td class=\"number\">1.50</td><td class=\"number\"></td><td class=\"number\">150.00 EUR</td></tr>
+150.00 EUR 10 15.00 EUR 165.00 EUR"
+        );
+    }
+
+    #[test]
+    fn default_template_has_all_placeholders() {
+        for placeholder in [
+            "{{number}}",
+            "{{date}}",
+            "{{client}}",
+            "{{month}}",
+            "{{lines}}",
+            "{{subtotal}}",
+            "{{vat_percent}}",
+            "{{vat}}",
+            "{{total}}",
+        ] {
+            assert!(DEFAULT_TEMPLATE.contains(placeholder), "{placeholder}");
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Columns and looks of the hours table
+use chrono::Duration;
+use comfy_table::presets::{ASCII_FULL, ASCII_MARKDOWN, ASCII_NO_BORDERS, UTF8_FULL};
+use serde::{Deserialize, Serialize};
+
+/// Column of the hours table
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    /// Project, client or total title
+    Project,
+    /// Hours tracked today
+    Today,
+    /// Hours of the current week and daily average
+    Week,
+    /// Hours of the current month and daily average
+    Month,
+    /// Daily, weekly and monthly targets
+    Targets,
+    /// Hours of the billable entries and their share
+    BillableHours,
+    /// Hours of the non-billable entries
+    NonBillableHours,
+    /// Billable amount with the hourly rates
+    BillableAmount,
+    /// Hours left to reach the monthly target
+    Remaining,
+    /// Hours at the end of the month with the current daily average
+    Forecast,
+}
+
+/// How durations are shown in the hours table
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum DurationFormat {
+    /// Hours and minutes, e.g. `7h 30m`
+    #[default]
+    HoursMinutes,
+    /// Decimal hours, e.g. `7.50`
+    Decimal,
+}
+
+impl DurationFormat {
+    /// Format duration aligned for columns. Empty when there is nothing to show
+    pub fn format(&self, duration: &Duration) -> String {
+        if duration.num_minutes() <= 0 {
+            return "".to_string();
+        }
+        match self {
+            DurationFormat::HoursMinutes => {
+                let hours = duration.num_hours();
+                let minutes = duration.num_minutes() - hours * 60;
+                format!("{:3}h {:2}m", hours, minutes)
+            }
+            DurationFormat::Decimal => {
+                format!("{:6.2}", duration.num_minutes() as f64 / 60.0)
+            }
+        }
+    }
+}
+
+/// Borders of the hours table
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Border {
+    /// Only lines between rows and columns
+    #[default]
+    None,
+    /// Full ASCII borders
+    Ascii,
+    /// Full borders with box drawing characters
+    Utf8,
+    /// Markdown table
+    Markdown,
+}
+
+impl Border {
+    /// Preset of the table library
+    pub fn preset(&self) -> &'static str {
+        match self {
+            Border::None => ASCII_NO_BORDERS,
+            Border::Ascii => ASCII_FULL,
+            Border::Utf8 => UTF8_FULL,
+            Border::Markdown => ASCII_MARKDOWN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        let duration = Duration::minutes(450);
+        assert_eq!(DurationFormat::HoursMinutes.format(&duration), "  7h 30m");
+        assert_eq!(DurationFormat::Decimal.format(&duration), "  7.50");
+        assert_eq!(DurationFormat::Decimal.format(&Duration::zero()), "");
+    }
+
+    #[test]
+    fn reads_columns_from_settings() {
+        #[derive(Deserialize)]
+        struct Table {
+            columns: Vec<Column>,
+            duration_format: DurationFormat,
+        }
+        let table: Table = toml::from_str(
+            "columns = [\"project\", \"billable-amount\", \"remaining\"]\nduration_format = \"decimal\"",
+        )
+        .unwrap();
+        assert_eq!(
+            table.columns,
+            vec![Column::Project, Column::BillableAmount, Column::Remaining]
+        );
+        assert_eq!(table.duration_format, DurationFormat::Decimal);
+    }
+}
@@ -0,0 +1,117 @@
+//! Rounding of billed hours
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Rounds durations to an increment, e.g. up to the next 15 minutes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding {
+    /// Increment in minutes, e.g. 15
+    pub increment_minutes: u32,
+    /// Which way to round, nearest if not given
+    #[serde(default)]
+    pub direction: RoundingDirection,
+    /// What is rounded, total hours if not given
+    #[serde(default)]
+    pub per: RoundingScope,
+}
+
+/// Which way durations are rounded
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingDirection {
+    /// Up to the next increment
+    Up,
+    /// Down to the previous increment
+    Down,
+    /// To the nearest increment, halves are rounded up
+    #[default]
+    Nearest,
+}
+
+/// Which durations are rounded
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingScope {
+    /// Each time entry
+    Entry,
+    /// Total hours of each day
+    Day,
+    /// Total hours of the whole period
+    #[default]
+    Total,
+}
+
+impl Rounding {
+    /// Round duration to whole minutes and then to the increment
+    pub fn apply(&self, duration: &Duration) -> Duration {
+        let minutes = duration.num_minutes();
+        let increment = self.increment_minutes as i64;
+        if increment <= 1 {
+            return Duration::minutes(minutes);
+        }
+        let rounded = match self.direction {
+            RoundingDirection::Up => (minutes + increment - 1) / increment * increment,
+            RoundingDirection::Down => minutes / increment * increment,
+            RoundingDirection::Nearest => (minutes + increment / 2) / increment * increment,
+        };
+        Duration::minutes(rounded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rounding(increment_minutes: u32, direction: RoundingDirection) -> Rounding {
+        Rounding {
+            increment_minutes,
+            direction,
+            per: RoundingScope::Total,
+        }
+    }
+
+    #[test]
+    fn rounds_to_increment() {
+        let duration = Duration::minutes(67);
+        assert_eq!(
+            rounding(15, RoundingDirection::Up).apply(&duration),
+            Duration::minutes(75)
+        );
+        assert_eq!(
+            rounding(15, RoundingDirection::Down).apply(&duration),
+            Duration::minutes(60)
+        );
+        assert_eq!(
+            rounding(15, RoundingDirection::Nearest).apply(&duration),
+            Duration::minutes(60)
+        );
+        assert_eq!(
+            rounding(15, RoundingDirection::Nearest).apply(&Duration::minutes(68)),
+            Duration::minutes(75)
+        );
+        assert_eq!(
+            rounding(15, RoundingDirection::Up).apply(&Duration::minutes(60)),
+            Duration::minutes(60)
+        );
+    }
+
+    #[test]
+    fn zero_increment_keeps_minutes() {
+        assert_eq!(
+            rounding(0, RoundingDirection::Up).apply(&Duration::seconds(125)),
+            Duration::minutes(2)
+        );
+    }
+
+    #[test]
+    fn parses_from_toml() {
+        let rounding: Rounding =
+            toml::from_str("increment_minutes = 30\ndirection = \"up\"").unwrap();
+        assert_eq!(rounding, self::rounding(30, RoundingDirection::Up));
+        let rounding: Rounding = toml::from_str("increment_minutes = 6").unwrap();
+        assert_eq!(rounding.direction, RoundingDirection::Nearest);
+        assert_eq!(rounding.per, RoundingScope::Total);
+        let rounding: Rounding = toml::from_str("increment_minutes = 6\nper = \"day\"").unwrap();
+        assert_eq!(rounding.per, RoundingScope::Day);
+    }
+}
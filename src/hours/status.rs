@@ -0,0 +1,263 @@
+//! One-line status of tracked hours for shell prompts and status bars
+//!
+//! Placeholders are `{today}`, `{week}` and `{month}` for the tracked hours, `{target_today}`,
+//! `{target_week}` and `{target_month}` for the targets and `{remaining_today}`,
+//! `{remaining_week}` and `{remaining_month}` for the hours left to reach the targets. Adding
+//! `:<project>` limits a placeholder to a single project, e.g. `{today:Website}`.
+use crate::hours::calendar;
+use crate::hours::table::format_short_duration;
+use crate::hours::target::TargetHours;
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::projects;
+use crate::settings::ProjectConfigs;
+use chrono::{Duration, NaiveDate, Weekday};
+
+/// Format used when no format is given
+pub static DEFAULT_FORMAT: &str = "{today}/{target_today} {week}/{target_week}";
+
+#[derive(Debug, Clone, Copy)]
+enum Period {
+    Today,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Figure {
+    Tracked,
+    Target,
+    Remaining,
+}
+
+/// Render the status line for `today`. Fails with unknown placeholders and projects
+pub fn render(
+    format: &str,
+    time_entries: &types::TimeEntries,
+    project_configs: &ProjectConfigs,
+    target_daily_hours: Option<TargetHours>,
+    today: &NaiveDate,
+) -> Result<String, String> {
+    let projects = time_entries.uniq_projects();
+    let mut output = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => return Err(format!("Missing '}}' in format: {}", format)),
+        };
+        let placeholder = &rest[start + 1..end];
+        let (name, project) = match placeholder.split_once(':') {
+            Some((name, project)) => (name, Some(project)),
+            None => (placeholder, None),
+        };
+        let (figure, period) = parse_placeholder(name)
+            .ok_or_else(|| format!("Unknown placeholder {{{}}}", placeholder))?;
+
+        let (tracked, target) = match project {
+            Some(query) => {
+                let key = projects::find_project_key(query, &projects, project_configs)?;
+                let tracked = match projects.iter().find(|project| project.key == key) {
+                    Some(project) => tracked_hours(project, period, today),
+                    None => Duration::zero(),
+                };
+                let config = project_configs.configs.get(&key);
+                let target = config.and_then(|config| match period {
+                    Period::Today => config.target_daily_hours,
+                    Period::Week => config.target_weekly_hours,
+                    Period::Month => config.target_monthly_hours,
+                });
+                (tracked, target)
+            }
+            None => (
+                tracked_hours(time_entries, period, today),
+                total_target(project_configs, target_daily_hours, period),
+            ),
+        };
+        output.push_str(&match figure {
+            Figure::Tracked => format_short_duration(&tracked),
+            Figure::Target => target
+                .map(|target| format_short_duration(&Duration::minutes(target.num_minutes())))
+                .unwrap_or_else(|| "-".to_string()),
+            Figure::Remaining => target
+                .map(|target| {
+                    let remaining = Duration::minutes(target.num_minutes()) - tracked;
+                    format_short_duration(&remaining.max(Duration::zero()))
+                })
+                .unwrap_or_else(|| "-".to_string()),
+        });
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn parse_placeholder(name: &str) -> Option<(Figure, Period)> {
+    let (figure, period) = match name.split_once('_') {
+        Some(("target", period)) => (Figure::Target, period),
+        Some(("remaining", period)) => (Figure::Remaining, period),
+        Some(_) => return None,
+        None => (Figure::Tracked, name),
+    };
+    let period = match period {
+        "today" => Period::Today,
+        "week" => Period::Week,
+        "month" => Period::Month,
+        _ => return None,
+    };
+    Some((figure, period))
+}
+
+fn tracked_hours(
+    entries: &impl TimeEntryCalculations,
+    period: Period,
+    today: &NaiveDate,
+) -> Duration {
+    let first_day = match period {
+        Period::Today => *today,
+        Period::Week => today.week(Weekday::Mon).first_day(),
+        Period::Month => return entries.total_hours(),
+    };
+    entries
+        .entries()
+        .iter()
+        .filter(|entry| {
+            entry
+                .start
+                .is_some_and(|start| (first_day..=*today).contains(&start.date_naive()))
+        })
+        .fold(Duration::zero(), |total, entry| total + entry.duration())
+}
+
+/// Daily target is the same as in the calendar. Weekly and monthly targets are sums of the
+/// targets of the visible projects
+fn total_target(
+    project_configs: &ProjectConfigs,
+    target_daily_hours: Option<TargetHours>,
+    period: Period,
+) -> Option<TargetHours> {
+    if let Period::Today = period {
+        return calendar::daily_target(target_daily_hours, project_configs);
+    }
+    let minutes: i64 = project_configs
+        .configs
+        .values()
+        .filter(|config| !config.is_hidden() && config.merged_into.is_none())
+        .filter_map(|config| match period {
+            Period::Week => config.target_weekly_hours,
+            _ => config.target_monthly_hours,
+        })
+        .map(|target| target.num_minutes())
+        .sum();
+    if minutes > 0 {
+        Some(TargetHours::from_minutes(minutes as u32))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ProjectConfig;
+    use crate::strict_string::ProjectName;
+    use chrono::{Local, TimeZone};
+    use std::collections::HashMap;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 1, 12).unwrap()
+    }
+
+    fn entry(project: &str, day: u32, minutes: i64) -> types::TimeEntry {
+        let start = Local.with_ymd_and_hms(2022, 1, day, 9, 0, 0).unwrap();
+        types::TimeEntry {
+            project: ProjectName::new(project),
+            start: Some(start),
+            end: Some(start + Duration::minutes(minutes)),
+            ..Default::default()
+        }
+    }
+
+    fn project_configs() -> ProjectConfigs {
+        ProjectConfigs {
+            configs: HashMap::from([(
+                entry("Website", 12, 0).project_key(),
+                ProjectConfig {
+                    target_daily_hours: Some(TargetHours::from_hours(4)),
+                    target_weekly_hours: Some(TargetHours::from_hours(20)),
+                    ..Default::default()
+                },
+            )]),
+        }
+    }
+
+    fn time_entries() -> types::TimeEntries {
+        types::TimeEntries {
+            entries: vec![
+                entry("Website", 12, 90),
+                entry("Backend", 12, 45),
+                entry("Backend", 10, 30),
+                entry("Backend", 7, 60),
+            ],
+        }
+    }
+
+    #[test]
+    fn renders_totals_and_targets() {
+        let status = render(
+            "{today}/{target_today} left {remaining_today}, month target {target_month}",
+            &time_entries(),
+            &project_configs(),
+            None,
+            &today(),
+        );
+        assert_eq!(
+            status,
+            Ok("2h15m/4h left 1h45m, month target -".to_string())
+        );
+
+        let status = render(
+            "{week} {month}",
+            &time_entries(),
+            &project_configs(),
+            None,
+            &today(),
+        );
+        assert_eq!(status, Ok("2h45m 3h45m".to_string()));
+
+        let status = render(
+            "{target_today}",
+            &time_entries(),
+            &project_configs(),
+            Some(TargetHours::from_minutes(450)),
+            &today(),
+        );
+        assert_eq!(status, Ok("7h30m".to_string()));
+    }
+
+    #[test]
+    fn renders_project_figures() {
+        let status = render(
+            "W {today:website}/{target_week:Website} B {week:Backend} {target_today:Backend}",
+            &time_entries(),
+            &project_configs(),
+            None,
+            &today(),
+        );
+        assert_eq!(status, Ok("W 1h30m/20h B 1h15m -".to_string()));
+    }
+
+    #[test]
+    fn fails_with_unknown_placeholders_and_projects() {
+        let render = |format| render(format, &time_entries(), &project_configs(), None, &today());
+        assert_eq!(
+            render("{year}"),
+            Err("Unknown placeholder {year}".to_string())
+        );
+        assert_eq!(
+            render("{today"),
+            Err("Missing '}' in format: {today".to_string())
+        );
+        assert!(render("{today:Unknown}").is_err());
+    }
+}
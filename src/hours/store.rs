@@ -1,12 +1,13 @@
 //! Storage module
 use crate::hours::types;
+use crate::settings::{self, LoadError};
 use crate::strict_string::FilePath;
 use std::fs;
 
 /// Common store functionality
 pub trait Store {
     fn save(&self, time_entries: &types::TimeEntries) -> Result<(), std::io::Error>;
-    fn load(&self) -> types::TimeEntries;
+    fn load(&self) -> Result<types::TimeEntries, LoadError>;
 }
 /// Filesystem storage for hours
 pub struct DiskStore {
@@ -20,14 +21,18 @@ impl Store for DiskStore {
         fs::write(self.path.as_str(), toml)
     }
 
-    fn load(&self) -> types::TimeEntries {
-        let hours_str = fs::read_to_string(self.path.as_str()).expect("Couldn't load hours");
+    /// Hours are empty when the file doesn't exist yet
+    fn load(&self) -> Result<types::TimeEntries, LoadError> {
+        let hours_str = settings::read_file(self.path.as_str())?;
         if hours_str.trim().is_empty() {
-            types::TimeEntries {
+            Ok(types::TimeEntries {
                 entries: Vec::new(),
-            }
+            })
         } else {
-            toml::from_str(&hours_str).unwrap()
+            toml::from_str(&hours_str).map_err(|err| LoadError::Parse {
+                path: self.path.to_string(),
+                message: err.to_string(),
+            })
         }
     }
 }
@@ -36,8 +41,9 @@ impl Store for DiskStore {
 mod tests {
     use super::super::*;
     use crate::strict_string::{Description, ProjectName};
+    use crate::settings::LoadError;
     use chrono::Duration;
-    use std::fs::File;
+    use std::fs::{self, File};
 
     #[test]
     fn return_empty_list_of_entries_by_default() {
@@ -47,10 +53,39 @@ mod tests {
             path: FilePath::new("/tmp/test.toml".to_string()),
         };
 
-        let loaded_entries = store.load();
+        let loaded_entries = store.load().unwrap();
 
         assert_eq!(loaded_entries.entries.len(), 0);
     }
+
+    #[test]
+    fn missing_file_is_loaded_as_empty_without_creating_it() {
+        let path = "/tmp/my-hours-missing-store.toml";
+        let _ = fs::remove_file(path);
+        let store = DiskStore {
+            path: FilePath::new(path),
+        };
+
+        assert_eq!(store.load().unwrap().entries.len(), 0);
+        assert!(!std::path::Path::new(path).exists());
+    }
+
+    #[test]
+    fn corrupt_file_fails_with_parse_error() {
+        let path = "/tmp/my-hours-corrupt-store.toml";
+        fs::write(path, "[[entries]]\ndescription = 1\n").unwrap();
+        let store = DiskStore {
+            path: FilePath::new(path),
+        };
+
+        match store.load() {
+            Err(LoadError::Parse { path: error_path, message }) => {
+                assert_eq!(error_path, path);
+                assert!(message.contains("line 2"), "{message}");
+            }
+            other => panic!("Expected parse error, got {:?}", other),
+        }
+    }
     #[test]
     fn saves_entries_into_file() {
         let filepath = FilePath::new("/tmp/test.toml".to_string());
@@ -64,6 +99,10 @@ mod tests {
                 client: None,
                 project: ProjectName::new("TestProject".to_string()),
                 billable_amount_cents: 0,
+                billable: Some(false),
+                tags: vec![],
+                task: None,
+                project_source: None,
                 start: Some(Local::now()),
                 end: Some(
                     Local::now()
@@ -74,7 +113,7 @@ mod tests {
             .to_vec(),
         };
         store.save(&time_entries).unwrap();
-        let loaded_entries = store.load();
+        let loaded_entries = store.load().unwrap();
         assert_eq!(loaded_entries.entries.len(), 1);
     }
 }
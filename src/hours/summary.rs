@@ -0,0 +1,313 @@
+//! Machine readable summary of tracked hours
+//!
+//! The schema is documented in README and changes to it bump [`SCHEMA_VERSION`].
+use crate::hours::billing;
+use crate::hours::rounding::Rounding;
+use crate::hours::target::{TargetHours, TargetStatus};
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::settings::{ClientConfigs, ProjectConfigs};
+use crate::strict_string::{ClientName, ProjectHash, ProjectName};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+/// Version of the summary schema
+pub static SCHEMA_VERSION: u32 = 1;
+
+/// Summary of the current month
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub schema_version: u32,
+    pub generated_at: DateTime<Local>,
+    pub projects: Vec<ProjectSummary>,
+    pub clients: Vec<ClientSummary>,
+    pub total: Hours,
+    pub total_billable_amount_cents: i64,
+    /// Target share of billable hours in percents, `null` when not set
+    pub target_billable_percent: Option<u8>,
+    /// Share of billable hours of the month compared to the target
+    pub billable_status: TargetStatus,
+    /// Currency from settings, `null` when not configured
+    pub currency: Option<String>,
+}
+
+/// Summary of single project
+#[derive(Debug, Serialize)]
+pub struct ProjectSummary {
+    pub key: ProjectHash,
+    pub client: Option<ClientName>,
+    pub project: ProjectName,
+    /// False when targets haven't been asked for the project yet
+    pub configured: bool,
+    pub hours: Hours,
+    /// Tracked minutes of the month with project's rounding rules
+    pub billed_minutes: i64,
+    pub billable_amount_cents: i64,
+    pub targets: Targets,
+    pub status: Statuses,
+}
+
+/// Summary of all projects of a client
+#[derive(Debug, Serialize)]
+pub struct ClientSummary {
+    pub client: Option<ClientName>,
+    pub hours: Hours,
+    pub billable_amount_cents: i64,
+    pub targets: Targets,
+    pub status: Statuses,
+}
+
+/// Tracked hours in minutes
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Hours {
+    pub today_minutes: i64,
+    pub week_minutes: i64,
+    pub week_daily_avg_minutes: i64,
+    pub month_minutes: i64,
+    pub month_daily_avg_minutes: i64,
+    pub month_billable_minutes: i64,
+    pub month_non_billable_minutes: i64,
+    /// Share of billable hours of the month, `null` when nothing is tracked
+    pub month_billable_percent: Option<i64>,
+}
+
+/// Targets in minutes, `null` when not set
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Targets {
+    pub daily_minutes: Option<i64>,
+    pub weekly_minutes: Option<i64>,
+    pub monthly_minutes: Option<i64>,
+}
+
+/// Status of today, current week and current month compared to the targets
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Statuses {
+    pub daily: TargetStatus,
+    pub weekly: TargetStatus,
+    pub monthly: TargetStatus,
+}
+
+/// Generate summary from the same entries and configs as the hours table
+pub fn generate_summary(
+    time_entries: &types::TimeEntries,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+    default_rounding: Option<Rounding>,
+    currency: &Option<String>,
+    target_billable_percent: Option<u8>,
+) -> Summary {
+    let projects = time_entries
+        .uniq_projects()
+        .iter()
+        .filter(|project| !project_configs.is_archived(&project.key))
+        .map(|project| {
+            let config = project_configs.get(project);
+            let (daily, weekly, monthly) = config
+                .map(|config| {
+                    (
+                        config.target_daily_hours,
+                        config.target_weekly_hours,
+                        config.target_monthly_hours,
+                    )
+                })
+                .unwrap_or((None, None, None));
+            ProjectSummary {
+                key: project.key.clone(),
+                client: project.client.clone(),
+                project: project.title.clone(),
+                configured: config.is_some(),
+                hours: Hours::new(project),
+                billed_minutes: billing::billed_hours(project, project_configs, default_rounding)
+                    .num_minutes(),
+                billable_amount_cents: billing::project_amount_cents(
+                    project,
+                    project_configs,
+                    client_configs,
+                    default_rounding,
+                ),
+                targets: Targets::new(daily, weekly, monthly),
+                status: Statuses::new(project, daily, weekly, monthly),
+            }
+        })
+        .collect();
+    let clients = time_entries
+        .uniq_clients()
+        .iter()
+        .map(|client| {
+            let (daily, weekly, monthly) = client_configs
+                .get(client)
+                .map(|config| {
+                    (
+                        config.target_daily_hours,
+                        config.target_weekly_hours,
+                        config.target_monthly_hours,
+                    )
+                })
+                .unwrap_or((None, None, None));
+            ClientSummary {
+                client: client.name.clone(),
+                hours: Hours::new(client),
+                billable_amount_cents: client
+                    .projects
+                    .iter()
+                    .map(|project| {
+                        billing::project_amount_cents(
+                            project,
+                            project_configs,
+                            client_configs,
+                            default_rounding,
+                        )
+                    })
+                    .sum(),
+                targets: Targets::new(daily, weekly, monthly),
+                status: Statuses::new(client, daily, weekly, monthly),
+            }
+        })
+        .collect();
+
+    Summary {
+        schema_version: SCHEMA_VERSION,
+        generated_at: Local::now(),
+        projects,
+        clients,
+        total: Hours::new(time_entries),
+        total_billable_amount_cents: billing::total_amount_cents(
+            time_entries,
+            project_configs,
+            client_configs,
+            default_rounding,
+        ),
+        target_billable_percent,
+        billable_status: TargetStatus::for_billable_percent(
+            target_billable_percent,
+            time_entries.billable_percent(),
+        ),
+        currency: currency.clone(),
+    }
+}
+
+impl Hours {
+    fn new(entries: &impl TimeEntryCalculations) -> Self {
+        Hours {
+            today_minutes: entries.total_hours_for_current_day().num_minutes(),
+            week_minutes: entries.total_hours_for_current_week().num_minutes(),
+            week_daily_avg_minutes: entries.daily_avg_for_current_week().num_minutes(),
+            month_minutes: entries.total_hours().num_minutes(),
+            month_daily_avg_minutes: entries.daily_avg_for_current_month().num_minutes(),
+            month_billable_minutes: entries.billable_hours().num_minutes(),
+            month_non_billable_minutes: entries.non_billable_hours().num_minutes(),
+            month_billable_percent: entries.billable_percent(),
+        }
+    }
+}
+
+impl Targets {
+    fn new(
+        daily: Option<TargetHours>,
+        weekly: Option<TargetHours>,
+        monthly: Option<TargetHours>,
+    ) -> Self {
+        Targets {
+            daily_minutes: daily.map(|target| target.num_minutes()),
+            weekly_minutes: weekly.map(|target| target.num_minutes()),
+            monthly_minutes: monthly.map(|target| target.num_minutes()),
+        }
+    }
+}
+
+impl Statuses {
+    fn new(
+        entries: &impl TimeEntryCalculations,
+        daily: Option<TargetHours>,
+        weekly: Option<TargetHours>,
+        monthly: Option<TargetHours>,
+    ) -> Self {
+        Statuses {
+            daily: TargetStatus::new(&daily, &entries.total_hours_for_current_day()),
+            weekly: TargetStatus::new(&weekly, &entries.total_hours_for_current_week()),
+            monthly: TargetStatus::new(&monthly, &entries.total_hours()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hours::rounding::{Rounding, RoundingDirection, RoundingScope};
+    use crate::settings::ProjectConfig;
+    use crate::strict_string::Description;
+    use chrono::Duration;
+    use std::collections::HashMap;
+
+    #[test]
+    fn summarises_projects_and_totals() {
+        let entry = |project: &str| types::TimeEntry {
+            description: Description::new("Description"),
+            project: ProjectName::new(project),
+            billable: Some(project == "Website"),
+            start: Some(Local::now()),
+            end: Some(Local::now() + Duration::minutes(90)),
+            ..Default::default()
+        };
+        let time_entries = types::TimeEntries {
+            entries: vec![entry("Website"), entry("Backend")],
+        };
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([(
+                entry("Website").project_key(),
+                ProjectConfig {
+                    target_daily_hours: Some(TargetHours::from_hours(2)),
+                    hourly_rate: Some(50.0),
+                    rounding: Some(Rounding {
+                        increment_minutes: 60,
+                        direction: RoundingDirection::Up,
+                        per: RoundingScope::Entry,
+                    }),
+                    ..Default::default()
+                },
+            )]),
+        };
+        let client_configs = ClientConfigs {
+            configs: HashMap::new(),
+        };
+
+        let summary = generate_summary(
+            &time_entries,
+            &project_configs,
+            &client_configs,
+            None,
+            &Some("EUR".to_string()),
+            Some(60),
+        );
+
+        assert_eq!(summary.schema_version, 1);
+        assert_eq!(summary.projects.len(), 2);
+        let backend = &summary.projects[0];
+        assert_eq!(backend.project, ProjectName::new("Backend"));
+        assert!(!backend.configured);
+        assert_eq!(backend.status.daily, TargetStatus::Unset);
+        let website = &summary.projects[1];
+        assert!(website.configured);
+        assert_eq!(website.hours.today_minutes, 90);
+        assert_eq!(website.targets.daily_minutes, Some(120));
+        assert_eq!(website.targets.weekly_minutes, None);
+        assert_eq!(website.status.daily, TargetStatus::Close);
+        assert_eq!(summary.clients.len(), 1);
+        assert_eq!(summary.total.month_minutes, 180);
+        assert_eq!(backend.billable_amount_cents, 0);
+        assert_eq!(backend.billed_minutes, 90);
+        assert_eq!(website.billed_minutes, 120);
+        assert_eq!(website.billable_amount_cents, 10000);
+        assert_eq!(summary.clients[0].billable_amount_cents, 10000);
+        assert_eq!(summary.total_billable_amount_cents, 10000);
+        assert_eq!(summary.total.month_billable_minutes, 90);
+        assert_eq!(summary.total.month_non_billable_minutes, 90);
+        assert_eq!(summary.total.month_billable_percent, Some(50));
+        assert_eq!(summary.billable_status, TargetStatus::Behind);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["projects"][1]["status"]["daily"], "close");
+        assert_eq!(json["projects"][1]["targets"]["weekly_minutes"], serde_json::Value::Null);
+        assert_eq!(json["total"]["today_minutes"], 180);
+        assert_eq!(json["currency"], "EUR");
+    }
+}
@@ -1,56 +1,472 @@
 //! Print time entries to terminal in table
+use crate::hours::billing;
+use crate::hours::layout::{Border, Column, DurationFormat};
+use crate::hours::rounding::Rounding;
+use crate::hours::target::{TargetHours, TargetStatus};
 use crate::hours::types::{self, TimeEntryCalculations};
-use crate::settings::{ProjectConfig, ProjectConfigs};
-use chrono::Duration;
+use crate::settings::{ClientConfig, ClientConfigs, ProjectConfig, ProjectConfigs};
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
 use comfy_table::{presets::ASCII_NO_BORDERS, Attribute, Cell, Color, Table};
 
+/// Columns and looks of the hours table
+pub struct TableOptions<'a> {
+    /// Shown columns in order. Billable columns are added to the default columns when used
+    pub columns: Option<Vec<Column>>,
+    pub duration_format: DurationFormat,
+    pub border: Border,
+    pub currency: &'a Option<String>,
+    pub target_billable_percent: Option<u8>,
+    /// Days off which are not counted in the forecast
+    pub holidays: &'a [NaiveDate],
+    /// Show target status as symbols, e.g. when colors are not used
+    pub status_symbols: bool,
+    /// Day the hours are shown for
+    pub today: NaiveDate,
+    /// Rounding of billed hours of projects which don't have their own rounding
+    pub default_rounding: Option<Rounding>,
+}
+
+impl Default for TableOptions<'_> {
+    fn default() -> Self {
+        TableOptions {
+            columns: None,
+            duration_format: DurationFormat::default(),
+            border: Border::default(),
+            currency: &None,
+            target_billable_percent: None,
+            holidays: &[],
+            status_symbols: false,
+            today: Local::now().date_naive(),
+            default_rounding: None,
+        }
+    }
+}
+
 /// Generate ASCII table of entries
 pub fn generate_table(
     time_entries: &types::TimeEntries,
     project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+    options: &TableOptions,
 ) -> Table {
+    let layout = Layout::new(time_entries, project_configs, client_configs, options);
+    let mut table = layout.new_table();
+
+    for project in time_entries.uniq_projects() {
+        if project_configs.is_archived(&project.key) {
+            continue;
+        }
+        let project_config = project_configs.get(&project);
+        table.add_row(layout.cells(&Row {
+            title: format_project_title(&project),
+            entries: &project,
+            targets: project_config.map(project_targets),
+            amount_cents: billing::project_amount_cents(
+                &project,
+                project_configs,
+                client_configs,
+                options.default_rounding,
+            ),
+            kind: RowKind::Project,
+        }));
+    }
+
+    table.add_row(layout.cells(&total_row(
+        time_entries,
+        billing::total_amount_cents(
+            time_entries,
+            project_configs,
+            client_configs,
+            options.default_rounding,
+        ),
+    )));
+
+    table
+}
+
+/// Generate ASCII table of entries where projects are grouped by their client
+pub fn generate_client_table(
+    time_entries: &types::TimeEntries,
+    project_configs: &ProjectConfigs,
+    client_configs: &ClientConfigs,
+    options: &TableOptions,
+) -> Table {
+    let layout = Layout::new(time_entries, project_configs, client_configs, options);
+    let mut table = layout.new_table();
+
+    for client in time_entries.uniq_clients() {
+        let mut client_amount = 0;
+        for project in client.projects.iter() {
+            let amount = billing::project_amount_cents(
+                project,
+                project_configs,
+                client_configs,
+                options.default_rounding,
+            );
+            client_amount += amount;
+            if project_configs.is_archived(&project.key) {
+                continue;
+            }
+            table.add_row(layout.cells(&Row {
+                title: project.title.to_string(),
+                entries: project,
+                targets: project_configs.get(project).map(project_targets),
+                amount_cents: amount,
+                kind: RowKind::Project,
+            }));
+        }
+        table.add_row(layout.cells(&Row {
+            title: format_client_title(&client),
+            entries: &client,
+            targets: Some(client_targets(client_configs.get(&client))),
+            amount_cents: client_amount,
+            kind: RowKind::Client,
+        }));
+    }
+
+    table.add_row(layout.cells(&total_row(
+        time_entries,
+        billing::total_amount_cents(
+            time_entries,
+            project_configs,
+            client_configs,
+            options.default_rounding,
+        ),
+    )));
+
+    table
+}
+
+/// Generate ASCII table of project's entries grouped by day and description, with daily subtotals
+pub fn generate_daily_breakdown_table(entries: &[types::TimeEntry]) -> Table {
     let mut table = Table::new();
     table.load_preset(ASCII_NO_BORDERS).set_header(vec![
-        header_cell("Project"),
-        header_cell("Today"),
-        header_cell("Current week / Daily AVG"),
-        header_cell("Current month / Daily AVG"),
-        header_cell("Target (day / week / month)"),
+        header_cell("Date"),
+        header_cell("Description"),
+        header_cell("Hours"),
     ]);
 
-    for project in time_entries.uniq_projects() {
-        let project_config = project_configs.get(&project).unwrap();
+    let mut dates: Vec<NaiveDate> = entries
+        .iter()
+        .map(|entry| entry.start.unwrap().date_naive())
+        .collect();
+    dates.sort();
+    dates.dedup();
+    for date in dates {
+        let day_entries = types::TimeEntries {
+            entries: entries
+                .iter()
+                .filter(|entry| entry.start.unwrap().date_naive() == date)
+                .cloned()
+                .collect(),
+        };
         table.add_row(vec![
-            Cell::new(format_project_title(&project)),
-            bold_cell(format_duration(&project.total_hours_for_current_day())).fg(
-                target_hours_color(
-                    &project_config.target_daily_hours,
-                    &project.total_hours_for_current_day(),
-                ),
-            ),
-            Cell::new(format_weekly_hours(&project)),
-            Cell::new(format_monthly_hours(&project)),
-            Cell::new(format_targets(project_config)),
+            bold_cell(date.format("%Y-%m-%d (%a)")),
+            Cell::new(""),
+            bold_cell(format_duration(&day_entries.total_hours())),
         ]);
+        for (description, duration) in description_totals(&day_entries.entries) {
+            table.add_row(vec![
+                Cell::new(""),
+                Cell::new(description),
+                Cell::new(format_duration(&duration)),
+            ]);
+        }
     }
 
+    let time_entries = types::TimeEntries {
+        entries: entries.to_vec(),
+    };
     table.add_row(vec![
         bold_cell("Total"),
-        bold_cell(format_duration(&time_entries.total_hours_for_current_day())),
-        bold_cell(format_hours(
-            &time_entries.total_hours_for_current_week(),
-            &time_entries.daily_avg_for_current_week(),
-        )),
-        bold_cell(format_hours(
-            &time_entries.total_hours(),
-            &time_entries.daily_avg_for_current_month(),
-        )),
         Cell::new(""),
+        bold_cell(format_duration(&time_entries.total_hours())),
     ]);
+    table
+}
 
+/// Generate ASCII table of project's hours per description, most hours first
+pub fn generate_description_breakdown_table(entries: &[types::TimeEntry]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(ASCII_NO_BORDERS)
+        .set_header(vec![header_cell("Description"), header_cell("Hours")]);
+    let mut totals = description_totals(entries);
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    for (description, duration) in totals {
+        table.add_row(vec![
+            Cell::new(description),
+            Cell::new(format_duration(&duration)),
+        ]);
+    }
     table
 }
 
+/// Total duration of each description in the order the descriptions first appear
+fn description_totals(entries: &[types::TimeEntry]) -> Vec<(String, Duration)> {
+    let mut entries: Vec<&types::TimeEntry> = entries.iter().collect();
+    entries.sort_by_key(|entry| entry.start);
+    let mut totals: Vec<(String, Duration)> = Vec::new();
+    for entry in entries {
+        let description = if entry.description.as_str().is_empty() {
+            "(no description)".to_string()
+        } else {
+            entry.description.to_string()
+        };
+        match totals.iter_mut().find(|(other, _)| other == &description) {
+            Some((_, duration)) => *duration += entry.duration(),
+            None => totals.push((description, entry.duration())),
+        }
+    }
+    totals
+}
+
+/// Daily, weekly and monthly target
+type Targets = (Option<TargetHours>, Option<TargetHours>, Option<TargetHours>);
+
+/// Row of the hours table. Targets are `None` for unconfigured projects
+struct Row<'a, T: TimeEntryCalculations> {
+    title: String,
+    entries: &'a T,
+    targets: Option<Targets>,
+    amount_cents: i64,
+    kind: RowKind,
+}
+
+#[derive(PartialEq, Eq)]
+enum RowKind {
+    Project,
+    Client,
+    Total,
+}
+
+/// Resolved columns of the table
+struct Layout<'a> {
+    columns: Vec<Column>,
+    duration_format: DurationFormat,
+    border: Border,
+    currency: &'a Option<String>,
+    target_billable_percent: Option<u8>,
+    remaining_work_days: i64,
+    status_symbols: bool,
+    today: NaiveDate,
+}
+
+impl<'a> Layout<'a> {
+    /// Billable columns are shown by default only when there is something to show in them
+    fn new(
+        time_entries: &types::TimeEntries,
+        project_configs: &ProjectConfigs,
+        client_configs: &ClientConfigs,
+        options: &TableOptions<'a>,
+    ) -> Self {
+        let columns = options.columns.clone().unwrap_or_else(|| {
+            let mut columns = vec![
+                Column::Project,
+                Column::Today,
+                Column::Week,
+                Column::Month,
+                Column::Targets,
+            ];
+            if is_billable_split_used(time_entries, options.target_billable_percent) {
+                columns.extend([Column::BillableHours, Column::NonBillableHours]);
+            }
+            if billing::is_billing_used(time_entries, project_configs, client_configs) {
+                columns.push(Column::BillableAmount);
+            }
+            columns
+        });
+        Layout {
+            columns,
+            duration_format: options.duration_format,
+            border: options.border,
+            currency: options.currency,
+            target_billable_percent: options.target_billable_percent,
+            remaining_work_days: remaining_work_days(&options.today, options.holidays),
+            status_symbols: options.status_symbols,
+            today: options.today,
+        }
+    }
+
+    fn new_table(&self) -> Table {
+        let mut table = Table::new();
+        let header: Vec<Cell> = self
+            .columns
+            .iter()
+            .map(|column| {
+                header_cell(match column {
+                    Column::Project => "Project".to_string(),
+                    Column::Today => "Today".to_string(),
+                    Column::Week => "Current week / Daily AVG".to_string(),
+                    Column::Month => "Current month / Daily AVG".to_string(),
+                    Column::Targets => "Target (day / week / month)".to_string(),
+                    Column::BillableHours => match self.target_billable_percent {
+                        Some(target) => format!("Billable hours (target {}%)", target),
+                        None => "Billable hours".to_string(),
+                    },
+                    Column::NonBillableHours => "Non-billable hours".to_string(),
+                    Column::BillableAmount => "Billable amount".to_string(),
+                    Column::Remaining => "Remaining (month)".to_string(),
+                    Column::Forecast => "Forecast (month)".to_string(),
+                })
+            })
+            .collect();
+        table.load_preset(self.border.preset()).set_header(header);
+        table
+    }
+
+    fn cells<T: TimeEntryCalculations>(&self, row: &Row<T>) -> Vec<Cell> {
+        self.columns
+            .iter()
+            .map(|column| self.cell(column, row))
+            .collect()
+    }
+
+    /// Hours of projects are bold only for today, client and total rows are bold on all columns
+    fn cell<T: TimeEntryCalculations>(&self, column: &Column, row: &Row<T>) -> Cell {
+        let cell = |content: String| match row.kind {
+            RowKind::Project => Cell::new(content),
+            RowKind::Client | RowKind::Total => bold_cell(content),
+        };
+        let (daily, weekly, monthly) = row.targets.unwrap_or((None, None, None));
+        let entries = row.entries;
+        let format = |duration: &Duration| self.duration_format.format(duration);
+        let status_cell = |cell: fn(String) -> Cell, content: String, status: TargetStatus| {
+            cell(self.with_symbol(content, status)).fg(status_color(status))
+        };
+        match column {
+            Column::Project => cell(row.title.clone()),
+            Column::Today => {
+                let today = entries.total_hours_for_day(&self.today);
+                status_cell(bold_cell, format(&today), TargetStatus::new(&daily, &today))
+            }
+            Column::Week => {
+                let week = entries.total_hours_for_week_of(&self.today);
+                let content = format_weekly_hours(entries, &self.today, self.duration_format);
+                match row.kind {
+                    RowKind::Client => {
+                        status_cell(bold_cell, content, TargetStatus::new(&weekly, &week))
+                    }
+                    _ => cell(content),
+                }
+            }
+            Column::Month => {
+                let month = entries.total_hours();
+                let content = format_monthly_hours(entries, self.duration_format);
+                match row.kind {
+                    RowKind::Client => {
+                        status_cell(bold_cell, content, TargetStatus::new(&monthly, &month))
+                    }
+                    _ => cell(content),
+                }
+            }
+            Column::Targets => match (&row.kind, row.targets) {
+                (RowKind::Total, _) => Cell::new(""),
+                (_, Some((daily, weekly, monthly))) => {
+                    Cell::new(format_target_hours(daily, weekly, monthly))
+                }
+                (_, None) => Cell::new("unconfigured").fg(Color::DarkGrey),
+            },
+            Column::BillableHours => {
+                let target = match row.kind {
+                    RowKind::Total => self.target_billable_percent,
+                    _ => None,
+                };
+                let status = TargetStatus::for_billable_percent(target, entries.billable_percent());
+                cell(self.with_symbol(format_billable_hours(entries, self.duration_format), status))
+                    .fg(status_color(status))
+            }
+            Column::NonBillableHours => cell(format(&entries.non_billable_hours())),
+            Column::BillableAmount => cell(format_amount(row.amount_cents, self.currency)),
+            Column::Remaining => cell(
+                monthly
+                    .map(|target| {
+                        let tracked = entries.total_hours().num_minutes();
+                        let remaining = Duration::minutes(target.num_minutes() - tracked);
+                        if remaining < Duration::zero() {
+                            format!("{} over", format(&-remaining))
+                        } else {
+                            format(&remaining)
+                        }
+                    })
+                    .unwrap_or_default(),
+            ),
+            Column::Forecast => {
+                let month = entries.total_hours();
+                let forecast = if month.is_zero() {
+                    month
+                } else {
+                    month + entries.daily_avg_for_current_month() * self.remaining_work_days as i32
+                };
+                let status = TargetStatus::new(&monthly, &forecast);
+                cell(self.with_symbol(format(&forecast), status)).fg(status_color(status))
+            }
+        }
+    }
+
+    /// Content followed by the status symbol when symbols are shown
+    fn with_symbol(&self, content: String, status: TargetStatus) -> String {
+        match status.symbol() {
+            symbol if !self.status_symbols || symbol.is_empty() => content,
+            symbol if content.is_empty() => symbol.to_string(),
+            symbol => format!("{} {}", content, symbol),
+        }
+    }
+}
+
+fn project_targets(project_config: &ProjectConfig) -> Targets {
+    (
+        project_config.target_daily_hours,
+        project_config.target_weekly_hours,
+        project_config.target_monthly_hours,
+    )
+}
+
+fn client_targets(client_config: Option<&ClientConfig>) -> Targets {
+    client_config
+        .map(|config| {
+            (
+                config.target_daily_hours,
+                config.target_weekly_hours,
+                config.target_monthly_hours,
+            )
+        })
+        .unwrap_or((None, None, None))
+}
+
+fn total_row(time_entries: &types::TimeEntries, amount_cents: i64) -> Row<'_, types::TimeEntries> {
+    Row {
+        title: "Total".to_string(),
+        entries: time_entries,
+        targets: Some((None, None, None)),
+        amount_cents,
+        kind: RowKind::Total,
+    }
+}
+
+/// Billable split is shown when any entry is billable or there is a target for it
+fn is_billable_split_used(
+    time_entries: &types::TimeEntries,
+    target_billable_percent: Option<u8>,
+) -> bool {
+    target_billable_percent.is_some()
+        || time_entries
+            .entries
+            .iter()
+            .any(|entry| entry.billable == Some(true))
+}
+
+/// Weekdays after today until the end of the month, holidays left out
+fn remaining_work_days(today: &NaiveDate, holidays: &[NaiveDate]) -> i64 {
+    today
+        .iter_days()
+        .skip(1)
+        .take_while(|date| date.month() == today.month())
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .filter(|date| !holidays.contains(date))
+        .count() as i64
+}
+
 fn header_cell<T: ToString>(content: T) -> Cell {
     bold_cell(content)
 }
@@ -59,58 +475,82 @@ fn bold_cell<T: ToString>(content: T) -> Cell {
     Cell::new(content).add_attribute(Attribute::Bold)
 }
 
-fn format_duration(duration: &chrono::Duration) -> String {
-    if duration.num_minutes() > 0 {
-        let hours = duration.num_hours();
-        let minutes = duration.num_minutes() - hours * 60;
-        format!("{:3}h {:2}m", hours, minutes)
-    } else {
-        "".to_string()
+/// Format duration as hours and minutes aligned for columns, e.g. `  7h 30m`
+pub fn format_duration(duration: &chrono::Duration) -> String {
+    DurationFormat::HoursMinutes.format(duration)
+}
+
+/// Format duration shortly without alignment, e.g. `7h30m`
+pub fn format_short_duration(duration: &chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    match (minutes / 60, minutes % 60) {
+        (hours, 0) => format!("{}h", hours),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h{:02}m", hours, minutes),
     }
 }
 
-fn target_hours_color(target_hours: &Option<u8>, duration: &chrono::Duration) -> Color {
-    if target_hours.is_some() {
-        let hours_as_i64 = target_hours.unwrap() as i64;
-        if &hours_as_i64 - 1 > duration.num_hours() {
-            Color::Red
-        } else if hours_as_i64 <= duration.num_hours() {
-            Color::Green
-        } else {
-            Color::Yellow
+fn status_color(status: TargetStatus) -> Color {
+    match status {
+        TargetStatus::Behind => Color::Red,
+        TargetStatus::Met => Color::Green,
+        TargetStatus::Close => Color::Yellow,
+        TargetStatus::Unset => Color::Reset,
+    }
+}
+
+fn format_billable_hours(entries: &impl TimeEntryCalculations, format: DurationFormat) -> String {
+    let billable_hours = entries.billable_hours();
+    match entries.billable_percent() {
+        Some(percent) if !billable_hours.is_zero() => {
+            format!("{} ({}%)", format.format(&billable_hours), percent)
         }
+        _ => format.format(&billable_hours),
+    }
+}
+
+fn format_amount(amount_cents: i64, currency: &Option<String>) -> String {
+    if amount_cents > 0 {
+        billing::format_amount(amount_cents, currency)
     } else {
-        Color::Reset
+        "".to_string()
     }
 }
 
-fn format_weekly_hours(project: &types::Project) -> String {
+fn format_weekly_hours(
+    entries: &impl TimeEntryCalculations,
+    today: &NaiveDate,
+    format: DurationFormat,
+) -> String {
     format_hours(
-        &project.total_hours_for_current_week(),
-        &project.daily_avg_for_current_week(),
+        &entries.total_hours_for_week_of(today),
+        &entries.daily_avg_for_week_of(today),
+        format,
     )
 }
 
-fn format_monthly_hours(project: &types::Project) -> String {
+fn format_monthly_hours(entries: &impl TimeEntryCalculations, format: DurationFormat) -> String {
     format_hours(
-        &project.total_hours(),
-        &project.daily_avg_for_current_month(),
+        &entries.total_hours(),
+        &entries.daily_avg_for_current_month(),
+        format,
     )
 }
 
-fn format_hours(total_hours: &Duration, avg_hours: &Duration) -> String {
+fn format_hours(total_hours: &Duration, avg_hours: &Duration, format: DurationFormat) -> String {
     if total_hours.is_zero() {
         "".to_string()
     } else {
         format!(
             "{} / {}",
-            &format_duration(total_hours),
-            &format_duration(avg_hours),
+            &format.format(total_hours),
+            &format.format(avg_hours),
         )
     }
 }
 
-fn format_project_title(project: &types::Project) -> String {
+/// Project title prefixed with the client, e.g. `Client / Project`
+pub fn format_project_title(project: &types::Project) -> String {
     project
         .client
         .as_ref()
@@ -118,57 +558,121 @@ fn format_project_title(project: &types::Project) -> String {
         .unwrap_or_else(|| project.title.to_string())
 }
 
-fn format_targets(project_config: &ProjectConfig) -> String {
-    if project_config.is_any_target_set() {
+fn format_client_title(client: &types::Client) -> String {
+    client
+        .name
+        .as_ref()
+        .map(|name| format!("{} total", name))
+        .unwrap_or_else(|| "No client total".to_string())
+}
+
+/// Format targets as `daily / weekly / monthly`. Empty when no target is set
+pub fn format_target_hours(
+    daily: Option<TargetHours>,
+    weekly: Option<TargetHours>,
+    monthly: Option<TargetHours>,
+) -> String {
+    if daily.is_some() || weekly.is_some() || monthly.is_some() {
         format!(
             "{} / {} / {}",
-            format_target_hour(project_config.target_daily_hours),
-            format_target_hour(project_config.target_weekly_hours),
-            format_target_hour(project_config.target_monthly_hours)
+            format_target_hour(daily),
+            format_target_hour(weekly),
+            format_target_hour(monthly)
         )
     } else {
         "".to_string()
     }
 }
 
-fn format_target_hour(target: Option<u8>) -> String {
+fn format_target_hour(target: Option<TargetHours>) -> String {
     target
-        .map(|hours| format!("{hours}h"))
+        .map(|hours| hours.to_string())
         .unwrap_or_else(|| "-".to_string())
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::hours::types;
+    use crate::strict_string::{Description, ProjectName};
+    use chrono::{Duration, Local, NaiveDate, TimeZone};
+
+    /// Day the tables are shown for, a Wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 1, 12).unwrap()
+    }
+
+    /// Entry of January 2022 starting at the hour of the day
+    fn entry(project: &str, day: u32, hour: u32, minutes: i64) -> types::TimeEntry {
+        let start = Local.with_ymd_and_hms(2022, 1, day, hour, 0, 0).unwrap();
+        types::TimeEntry {
+            description: Description::new("Description"),
+            project: ProjectName::new(project),
+            start: Some(start),
+            end: Some(start + Duration::minutes(minutes)),
+            ..Default::default()
+        }
+    }
 
     mod colors {
         use super::super::*;
 
         #[test]
         fn it_gives_hours_color() {
+            let target_hours_color = |target_hours: &Option<TargetHours>, duration: &Duration| {
+                status_color(TargetStatus::new(target_hours, duration))
+            };
             assert_eq!(target_hours_color(&None, &Duration::hours(0)), Color::Reset);
             assert_eq!(
-                target_hours_color(&Some(0), &Duration::hours(0)),
+                target_hours_color(&Some(TargetHours::from_hours(0)), &Duration::hours(0)),
                 Color::Green
             );
             assert_eq!(
-                target_hours_color(&Some(1), &Duration::hours(3)),
+                target_hours_color(&Some(TargetHours::from_hours(1)), &Duration::hours(3)),
                 Color::Green
             );
             assert_eq!(
-                target_hours_color(&Some(3), &Duration::hours(1)),
+                target_hours_color(&Some(TargetHours::from_hours(3)), &Duration::hours(1)),
                 Color::Red
             );
             assert_eq!(
-                target_hours_color(&Some(2), &Duration::hours(1)),
+                target_hours_color(&Some(TargetHours::from_hours(2)), &Duration::hours(1)),
+                Color::Yellow
+            );
+            assert_eq!(
+                target_hours_color(&Some(TargetHours::from_minutes(450)), &Duration::minutes(449)),
                 Color::Yellow
             );
+            assert_eq!(
+                target_hours_color(&Some(TargetHours::from_minutes(450)), &Duration::minutes(450)),
+                Color::Green
+            );
+            assert_eq!(
+                target_hours_color(&Some(TargetHours::from_minutes(450)), &Duration::minutes(389)),
+                Color::Red
+            );
+        }
+    }
+
+    mod billable_colors {
+        use super::super::*;
+
+        #[test]
+        fn it_gives_billable_percent_color() {
+            let billable_percent_color =
+                |target, percent| status_color(TargetStatus::for_billable_percent(target, percent));
+            assert_eq!(billable_percent_color(None, Some(50)), Color::Reset);
+            assert_eq!(billable_percent_color(Some(75), None), Color::Reset);
+            assert_eq!(billable_percent_color(Some(75), Some(75)), Color::Green);
+            assert_eq!(billable_percent_color(Some(75), Some(74)), Color::Red);
         }
     }
 
     mod formats {
         use super::super::*;
-        use chrono::Local;
-        use crate::strict_string::{ProjectHash, ProjectName, Description, ClientName};
+        use super::{entry, today};
+        use crate::strict_string::{ProjectHash, ProjectName, ClientName};
+
+        const HOURS_MINUTES: DurationFormat = DurationFormat::HoursMinutes;
 
         #[test]
         fn it_formats_weekly_monthly_hours() {
@@ -178,8 +682,8 @@ mod tests {
                 key: ProjectHash::new("".to_string()),
                 entries: vec![],
             };
-            assert_eq!(format_monthly_hours(&project), "");
-            assert_eq!(format_weekly_hours(&project), "");
+            assert_eq!(format_monthly_hours(&project, HOURS_MINUTES), "");
+            assert_eq!(format_weekly_hours(&project, &today(), HOURS_MINUTES), "");
 
             let mut project = types::Project {
                 client: None,
@@ -188,40 +692,30 @@ mod tests {
                 entries: vec![],
             };
 
-            let today = Local::now().date_naive();
-            let start = today
-                .and_hms_milli_opt(1, 0, 00, 0)
-                .unwrap()
-                .and_local_timezone(Local)
-                .unwrap();
-            let end = today
-                .and_hms_milli_opt(13, 0, 0, 0)
-                .unwrap()
-                .and_local_timezone(Local)
-                .unwrap();
-
-            project.entries.push(types::TimeEntry {
-                description: Description::new("Monday".to_string()),
-                client: None,
-                project: ProjectName::new("my-hours".to_string()),
-                start: Some(start),
-                end: Some(end),
-                billable_amount_cents: 1,
-            });
+            project.entries.push(entry("my-hours", 12, 1, 12 * 60));
 
-            assert_eq!(format_monthly_hours(&project), " 12h  0m /  12h  0m");
-            assert_eq!(format_weekly_hours(&project), " 12h  0m /  12h  0m");
+            assert_eq!(
+                format_monthly_hours(&project, HOURS_MINUTES),
+                " 12h  0m /  12h  0m"
+            );
+            assert_eq!(
+                format_weekly_hours(&project, &today(), HOURS_MINUTES),
+                " 12h  0m /  12h  0m"
+            );
         }
 
         #[test]
         fn it_formats_hours() {
             assert_eq!(
-                format_hours(&Duration::hours(1), &Duration::hours(2)),
+                format_hours(&Duration::hours(1), &Duration::hours(2), HOURS_MINUTES),
                 "  1h  0m /   2h  0m"
             );
-            assert_eq!(format_hours(&Duration::hours(0), &Duration::hours(2)), "");
             assert_eq!(
-                format_hours(&Duration::hours(1), &Duration::hours(0)),
+                format_hours(&Duration::hours(0), &Duration::hours(2), HOURS_MINUTES),
+                ""
+            );
+            assert_eq!(
+                format_hours(&Duration::hours(1), &Duration::hours(0), HOURS_MINUTES),
                 "  1h  0m / "
             );
         }
@@ -251,52 +745,60 @@ mod tests {
         #[test]
         fn it_formats_targets() {
             let config = ProjectConfig {
-                target_daily_hours: Some(1),
-                target_weekly_hours: Some(2),
-                target_monthly_hours: Some(3),
+                target_daily_hours: Some(TargetHours::from_hours(1)),
+                target_weekly_hours: Some(TargetHours::from_hours(2)),
+                target_monthly_hours: Some(TargetHours::from_hours(3)),
+                ..Default::default()
             };
 
+            let format_targets = |config: &ProjectConfig| {
+                let (daily, weekly, monthly) = project_targets(config);
+                format_target_hours(daily, weekly, monthly)
+            };
             assert_eq!(format_targets(&config), "1h / 2h / 3h");
 
             let config_without_targets = ProjectConfig {
                 target_daily_hours: None,
                 target_weekly_hours: None,
                 target_monthly_hours: None,
+                ..Default::default()
             };
             assert_eq!(format_targets(&config_without_targets), "");
         }
 
         #[test]
         fn it_formats_target_hours() {
-            assert_eq!(format_target_hour(Some(1)), "1h");
+            assert_eq!(format_target_hour(Some(TargetHours::from_hours(1))), "1h");
+            assert_eq!(format_target_hour(Some(TargetHours::from_minutes(450))), "7h30m");
             assert_eq!(format_target_hour(None), "-");
         }
     }
 
     mod formats_table {
         use super::super::*;
-        use chrono::Local;
+        use super::{entry, today};
+
+        fn no_client_configs() -> ClientConfigs {
+            ClientConfigs {
+                configs: HashMap::new(),
+            }
+        }
         use sha2::{Digest, Sha256};
         use std::collections::HashMap;
-        use crate::strict_string::{ProjectName, Description, ProjectHash};
+        use crate::strict_string::{ClientName, ProjectName, ProjectHash};
+
+        fn options<'a>() -> TableOptions<'a> {
+            TableOptions {
+                today: today(),
+                ..Default::default()
+            }
+        }
 
         #[test]
         fn formats_table_with_one_project_work_done_current_day_no_targets() {
             let project_name = ProjectName::new("Project".to_string());
             let time_entries = types::TimeEntries {
-                entries: [types::TimeEntry {
-                    description: Description::new("Description".to_string()),
-                    client: None,
-                    project: project_name.clone(),
-                    billable_amount_cents: 0,
-                    start: Some(Local::now()),
-                    end: Some(
-                        Local::now()
-                            .checked_add_signed(Duration::minutes(60))
-                            .unwrap(),
-                    ),
-                }]
-                .to_vec(),
+                entries: vec![entry(project_name.as_str(), 12, 9, 60)],
             };
             let mut hasher = Sha256::new();
             hasher.update(project_name.as_str());
@@ -309,10 +811,16 @@ mod tests {
                         target_daily_hours: None,
                         target_weekly_hours: None,
                         target_monthly_hours: None,
+                        ..Default::default()
                     },
                 )]),
             };
-            let mut hours_table = generate_table(&time_entries, &project_configs);
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &options(),
+            );
             hours_table.force_no_tty();
             let expected = "
  Project | Today    | Current week / Daily AVG | Current month / Daily AVG | Target (day / week / month) 
@@ -323,5 +831,397 @@ mod tests {
 
             assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
         }
+
+        #[test]
+        fn archived_projects_are_counted_only_in_totals() {
+            let entry = |project: &str| entry(project, 12, 9, 60);
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Project"), entry("Old project")],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([
+                    (entry("Project").project_key(), ProjectConfig::default()),
+                    (
+                        entry("Old project").project_key(),
+                        ProjectConfig {
+                            archived: true,
+                            ..Default::default()
+                        },
+                    ),
+                ]),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &options(),
+            );
+            hours_table.force_no_tty();
+            let expected = "
+ Project | Today    | Current week / Daily AVG | Current month / Daily AVG | Target (day / week / month) 
+=========================================================================================================
+ Project |   1h  0m |   1h  0m /   1h  0m      |   1h  0m /   1h  0m       |                             
+---------+----------+--------------------------+---------------------------+-----------------------------
+ Total   |   2h  0m |   2h  0m /   2h  0m      |   2h  0m /   2h  0m       |                             ";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn shows_billable_amounts_when_rates_are_set() {
+            let entry = |project: &str, billable_amount_cents: usize| types::TimeEntry {
+                billable_amount_cents,
+                ..entry(project, 12, 9, 90)
+            };
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Backend", 5000), entry("Website", 0)],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([
+                    (entry("Backend", 0).project_key(), ProjectConfig::default()),
+                    (
+                        entry("Website", 0).project_key(),
+                        ProjectConfig {
+                            hourly_rate: Some(100.0),
+                            ..Default::default()
+                        },
+                    ),
+                ]),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &TableOptions {
+                    currency: &Some("EUR".to_string()),
+                    ..options()
+                },
+            );
+            hours_table.force_no_tty();
+            let expected = "
+ Project | Today    | Current week / Daily AVG | Current month / Daily AVG | Target (day / week / month) | Billable amount 
+===========================================================================================================================
+ Backend |   1h 30m |   1h 30m /   1h 30m      |   1h 30m /   1h 30m       |                             | 50.00 EUR       
+---------+----------+--------------------------+---------------------------+-----------------------------+-----------------
+ Website |   1h 30m |   1h 30m /   1h 30m      |   1h 30m /   1h 30m       |                             | 150.00 EUR      
+---------+----------+--------------------------+---------------------------+-----------------------------+-----------------
+ Total   |   3h  0m |   3h  0m /   3h  0m      |   3h  0m /   3h  0m       |                             | 200.00 EUR      ";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn shows_billable_split_with_target() {
+            let entry = |project: &str, billable: bool| types::TimeEntry {
+                billable: Some(billable),
+                ..entry(project, 12, 9, 60)
+            };
+            let time_entries = types::TimeEntries {
+                entries: vec![
+                    entry("Website", true),
+                    entry("Website", true),
+                    entry("Website", true),
+                    entry("Internal", false),
+                ],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([
+                    (entry("Website", true).project_key(), ProjectConfig::default()),
+                    (entry("Internal", false).project_key(), ProjectConfig::default()),
+                ]),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &TableOptions {
+                    target_billable_percent: Some(80),
+                    ..options()
+                },
+            );
+            hours_table.force_no_tty();
+            let expected = "
+ Project  | Today    | Current week / Daily AVG | Current month / Daily AVG | Target (day / week / month) | Billable hours (target 80%) | Non-billable hours 
+=============================================================================================================================================================
+ Internal |   1h  0m |   1h  0m /   1h  0m      |   1h  0m /   1h  0m       |                             |                             |   1h  0m           
+----------+----------+--------------------------+---------------------------+-----------------------------+-----------------------------+--------------------
+ Website  |   3h  0m |   3h  0m /   3h  0m      |   3h  0m /   3h  0m       |                             |   3h  0m (100%)             |                    
+----------+----------+--------------------------+---------------------------+-----------------------------+-----------------------------+--------------------
+ Total    |   4h  0m |   4h  0m /   4h  0m      |   4h  0m /   4h  0m       |                             |   3h  0m (75%)              |   1h  0m           ";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn formats_table_with_unconfigured_project() {
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Project", 12, 9, 60)],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::new(),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &options(),
+            );
+            hours_table.force_no_tty();
+            let expected = "
+ Project | Today    | Current week / Daily AVG | Current month / Daily AVG | Target (day / week / month) 
+=========================================================================================================
+ Project |   1h  0m |   1h  0m /   1h  0m      |   1h  0m /   1h  0m       | unconfigured                
+---------+----------+--------------------------+---------------------------+-----------------------------
+ Total   |   1h  0m |   1h  0m /   1h  0m      |   1h  0m /   1h  0m       |                             ";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn formats_table_grouped_by_client_with_subtotals() {
+            let entry = |client: &str, project: &ProjectName| types::TimeEntry {
+                client: Some(ClientName::new(client)),
+                ..entry(project.as_str(), 12, 9, 60)
+            };
+            let website = ProjectName::new("Website".to_string());
+            let backend = ProjectName::new("Backend".to_string());
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Acme", &website), entry("Acme", &backend)],
+            };
+            let project_key = |project_name: &ProjectName| {
+                let mut hasher = Sha256::new();
+                hasher.update(project_name.as_str());
+                ProjectHash::new(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect::<String>())
+            };
+            let no_targets = ProjectConfig {
+                target_daily_hours: None,
+                target_weekly_hours: None,
+                target_monthly_hours: None,
+                ..Default::default()
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([
+                    (project_key(&website), no_targets.clone()),
+                    (project_key(&backend), no_targets),
+                ]),
+            };
+            let client_configs = ClientConfigs {
+                configs: HashMap::from([(
+                    ClientName::new("Acme"),
+                    ClientConfig {
+                        target_daily_hours: Some(TargetHours::from_hours(2)),
+                        target_weekly_hours: None,
+                        target_monthly_hours: Some(TargetHours::from_hours(100)),
+                        ..Default::default()
+                    },
+                )]),
+            };
+            let mut hours_table = generate_client_table(
+                &time_entries,
+                &project_configs,
+                &client_configs,
+                &options(),
+            );
+            hours_table.force_no_tty();
+            let expected = "
+ Project    | Today    | Current week / Daily AVG | Current month / Daily AVG | Target (day / week / month) 
+============================================================================================================
+ Backend    |   1h  0m |   1h  0m /   1h  0m      |   1h  0m /   1h  0m       |                             
+------------+----------+--------------------------+---------------------------+-----------------------------
+ Website    |   1h  0m |   1h  0m /   1h  0m      |   1h  0m /   1h  0m       |                             
+------------+----------+--------------------------+---------------------------+-----------------------------
+ Acme total |   2h  0m |   2h  0m /   2h  0m      |   2h  0m /   2h  0m       | 2h / - / 100h               
+------------+----------+--------------------------+---------------------------+-----------------------------
+ Total      |   2h  0m |   2h  0m /   2h  0m      |   2h  0m /   2h  0m       |                             ";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn shows_configured_columns_in_order() {
+            let entry = |project: &str, minutes: i64| entry(project, 12, 9, minutes);
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Website", 90), entry("Internal", 30)],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([(
+                    entry("Website", 0).project_key(),
+                    ProjectConfig {
+                        target_monthly_hours: Some(TargetHours::from_hours(10)),
+                        ..Default::default()
+                    },
+                )]),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &TableOptions {
+                    columns: Some(vec![
+                        Column::Project,
+                        Column::Remaining,
+                        Column::Today,
+                        Column::Targets,
+                    ]),
+                    duration_format: DurationFormat::Decimal,
+                    border: Border::Markdown,
+                    ..options()
+                },
+            );
+            hours_table.force_no_tty();
+            let expected = "
+| Project  | Remaining (month) | Today  | Target (day / week / month) |
+|----------|-------------------|--------|-----------------------------|
+| Internal |                   |   0.50 | unconfigured                |
+| Website  |   8.50            |   1.50 | - / - / 10h                 |
+| Total    |                   |   2.00 |                             |";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn shows_hours_over_monthly_target_as_remaining() {
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Website", 12, 9, 90)],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([(
+                    entry("Website", 12, 9, 0).project_key(),
+                    ProjectConfig {
+                        target_monthly_hours: Some(TargetHours::from_hours(1)),
+                        ..Default::default()
+                    },
+                )]),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &TableOptions {
+                    columns: Some(vec![Column::Project, Column::Remaining]),
+                    border: Border::Markdown,
+                    ..options()
+                },
+            );
+            hours_table.force_no_tty();
+            let expected = "
+| Project | Remaining (month) |
+|---------|-------------------|
+| Website |   0h 30m over     |
+| Total   |                   |";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn shows_status_symbols_without_colors() {
+            let entry = |project: &str| entry(project, 12, 9, 60);
+            let config = |hours: u32| ProjectConfig {
+                target_daily_hours: Some(TargetHours::from_hours(hours)),
+                ..Default::default()
+            };
+            let time_entries = types::TimeEntries {
+                entries: vec![entry("Backend"), entry("Internal"), entry("Website")],
+            };
+            let project_configs = ProjectConfigs {
+                configs: HashMap::from([
+                    (entry("Backend").project_key(), config(1)),
+                    (entry("Internal").project_key(), config(2)),
+                    (entry("Website").project_key(), config(3)),
+                ]),
+            };
+            let mut hours_table = generate_table(
+                &time_entries,
+                &project_configs,
+                &no_client_configs(),
+                &TableOptions {
+                    columns: Some(vec![Column::Project, Column::Today]),
+                    status_symbols: true,
+                    ..options()
+                },
+            );
+            hours_table.force_no_tty();
+            let expected = "
+ Project  | Today      
+=======================
+ Backend  |   1h  0m ✓ 
+----------+------------
+ Internal |   1h  0m ! 
+----------+------------
+ Website  |   1h  0m ✗ 
+----------+------------
+ Total    |   3h  0m   ";
+
+            assert_eq!("\n".to_string() + &hours_table.to_string(), expected)
+        }
+
+        #[test]
+        fn counts_remaining_work_days_of_month() {
+            let today = NaiveDate::from_ymd_opt(2022, 2, 23).unwrap();
+            assert_eq!(remaining_work_days(&today, &[]), 3);
+            let holidays = [NaiveDate::from_ymd_opt(2022, 2, 24).unwrap()];
+            assert_eq!(remaining_work_days(&today, &holidays), 2);
+        }
+    }
+
+    mod formats_breakdown {
+        use super::super::*;
+        use crate::strict_string::Description;
+
+        fn entries() -> Vec<types::TimeEntry> {
+            let entry = |day: u32, hour: u32, minutes: i64, description: &str| types::TimeEntry {
+                description: Description::new(description),
+                ..super::entry("Website", day, hour, minutes)
+            };
+            vec![
+                entry(12, 13, 30, "Planning"),
+                entry(11, 9, 90, "Login form"),
+                entry(12, 9, 60, "Login form"),
+                entry(12, 15, 45, ""),
+                entry(11, 13, 30, "Planning"),
+            ]
+        }
+
+        #[test]
+        fn groups_entries_by_day_and_description() {
+            let mut table = generate_daily_breakdown_table(&entries());
+            table.force_no_tty();
+            let expected = "
+ Date             | Description      | Hours    
+================================================
+ 2022-01-11 (Tue) |                  |   2h  0m 
+------------------+------------------+----------
+                  | Login form       |   1h 30m 
+------------------+------------------+----------
+                  | Planning         |   0h 30m 
+------------------+------------------+----------
+ 2022-01-12 (Wed) |                  |   2h 15m 
+------------------+------------------+----------
+                  | Login form       |   1h  0m 
+------------------+------------------+----------
+                  | Planning         |   0h 30m 
+------------------+------------------+----------
+                  | (no description) |   0h 45m 
+------------------+------------------+----------
+ Total            |                  |   4h 15m ";
+
+            assert_eq!("\n".to_string() + &table.to_string(), expected)
+        }
+
+        #[test]
+        fn sums_descriptions_most_hours_first() {
+            let mut table = generate_description_breakdown_table(&entries());
+            table.force_no_tty();
+            let expected = "
+ Description      | Hours    
+=============================
+ Login form       |   2h 30m 
+------------------+----------
+ Planning         |   1h  0m 
+------------------+----------
+ (no description) |   0h 45m ";
+
+            assert_eq!("\n".to_string() + &table.to_string(), expected)
+        }
     }
 }
@@ -0,0 +1,238 @@
+//! Target hours for projects and clients
+use chrono::Duration;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Targeted amount of time with minute precision.
+///
+/// Parsed from strings like `7h30m`, `7h`, `45m` or decimal hours like `7.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TargetHours {
+    minutes: u32,
+}
+
+/// How tracked hours compare to the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetStatus {
+    /// Target has been reached
+    Met,
+    /// Less than an hour left to reach the target
+    Close,
+    /// More than an hour left to reach the target
+    Behind,
+    /// No target set
+    Unset,
+}
+
+/// Error returned when target hours can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTargetHoursError(String);
+
+impl TargetHours {
+    pub fn from_minutes(minutes: u32) -> Self {
+        Self { minutes }
+    }
+
+    pub fn from_hours(hours: u32) -> Self {
+        Self::from_minutes(hours * 60)
+    }
+
+    pub fn num_minutes(&self) -> i64 {
+        self.minutes as i64
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minutes == 0
+    }
+
+    fn from_decimal_hours(hours: f64) -> Option<Self> {
+        if hours.is_finite() && hours >= 0.0 && hours * 60.0 <= u32::MAX as f64 {
+            Some(Self::from_minutes((hours * 60.0).round() as u32))
+        } else {
+            None
+        }
+    }
+}
+
+impl TargetStatus {
+    pub fn new(target: &Option<TargetHours>, tracked: &Duration) -> Self {
+        match target {
+            Some(target) => {
+                let target_minutes = target.num_minutes();
+                if target_minutes - 60 > tracked.num_minutes() {
+                    TargetStatus::Behind
+                } else if target_minutes <= tracked.num_minutes() {
+                    TargetStatus::Met
+                } else {
+                    TargetStatus::Close
+                }
+            }
+            None => TargetStatus::Unset,
+        }
+    }
+
+    /// Status of the billable share in percents. Billable share has no close state, it's either
+    /// met or behind the target. Status is unset also when nothing is tracked
+    pub fn for_billable_percent(target: Option<u8>, percent: Option<i64>) -> Self {
+        match (target, percent) {
+            (Some(target), Some(percent)) if percent >= target as i64 => TargetStatus::Met,
+            (Some(_), Some(_)) => TargetStatus::Behind,
+            _ => TargetStatus::Unset,
+        }
+    }
+
+    /// Symbol showing the status without colors, empty when there is no target
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TargetStatus::Met => "✓",
+            TargetStatus::Close => "!",
+            TargetStatus::Behind => "✗",
+            TargetStatus::Unset => "",
+        }
+    }
+}
+
+impl FromStr for TargetHours {
+    type Err = ParseTargetHoursError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || ParseTargetHoursError(input.to_string());
+        let value = input.trim().to_lowercase();
+        if value.is_empty() {
+            return Err(error());
+        }
+
+        if !value.contains('h') && !value.contains('m') {
+            return value
+                .parse::<f64>()
+                .ok()
+                .and_then(Self::from_decimal_hours)
+                .ok_or_else(error);
+        }
+
+        let (hours, rest) = match value.split_once('h') {
+            Some((hours, rest)) => (hours.trim(), rest.trim()),
+            None => ("", value.as_str()),
+        };
+        let minutes = match rest.strip_suffix('m') {
+            Some(minutes) => minutes.trim(),
+            None if rest.is_empty() => "",
+            None => return Err(error()),
+        };
+        if hours.is_empty() && minutes.is_empty() {
+            return Err(error());
+        }
+        let minutes = if minutes.is_empty() {
+            0
+        } else {
+            minutes.parse::<u32>().map_err(|_| error())?
+        };
+        let hours = if hours.is_empty() {
+            Self::from_minutes(0)
+        } else {
+            hours
+                .parse::<f64>()
+                .ok()
+                .and_then(Self::from_decimal_hours)
+                .ok_or_else(error)?
+        };
+        hours
+            .minutes
+            .checked_add(minutes)
+            .map(Self::from_minutes)
+            .ok_or_else(error)
+    }
+}
+
+impl fmt::Display for TargetHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hours = self.minutes / 60;
+        let minutes = self.minutes % 60;
+        match (hours, minutes) {
+            (hours, 0) => write!(f, "{}h", hours),
+            (0, minutes) => write!(f, "{}m", minutes),
+            (hours, minutes) => write!(f, "{}h{}m", hours, minutes),
+        }
+    }
+}
+
+impl fmt::Display for ParseTargetHoursError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is not a valid target, use for example 7h30m, 45m or 7.5",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseTargetHoursError {}
+
+impl Serialize for TargetHours {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TargetHours {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hours_and_minutes() {
+        assert_eq!("7h30m".parse(), Ok(TargetHours::from_minutes(450)));
+        assert_eq!("7h 30m".parse(), Ok(TargetHours::from_minutes(450)));
+        assert_eq!("8h".parse(), Ok(TargetHours::from_hours(8)));
+        assert_eq!("45m".parse(), Ok(TargetHours::from_minutes(45)));
+        assert_eq!("300H".parse(), Ok(TargetHours::from_hours(300)));
+    }
+
+    #[test]
+    fn parses_decimal_hours() {
+        assert_eq!("7.5".parse(), Ok(TargetHours::from_minutes(450)));
+        assert_eq!("8".parse(), Ok(TargetHours::from_hours(8)));
+        assert_eq!("0.25".parse(), Ok(TargetHours::from_minutes(15)));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for input in ["", "abc", "h", "7h30", "-1", "7m30h", "1.5m"] {
+            assert!(input.parse::<TargetHours>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn compares_tracked_time_to_target() {
+        let target = Some(TargetHours::from_minutes(450));
+        assert_eq!(TargetStatus::new(&None, &Duration::hours(1)), TargetStatus::Unset);
+        assert_eq!(TargetStatus::new(&target, &Duration::minutes(450)), TargetStatus::Met);
+        assert_eq!(TargetStatus::new(&target, &Duration::minutes(390)), TargetStatus::Close);
+        assert_eq!(TargetStatus::new(&target, &Duration::minutes(389)), TargetStatus::Behind);
+    }
+
+    #[test]
+    fn compares_billable_percent_to_target() {
+        let status = TargetStatus::for_billable_percent;
+        assert_eq!(status(None, Some(50)), TargetStatus::Unset);
+        assert_eq!(status(Some(75), None), TargetStatus::Unset);
+        assert_eq!(status(Some(75), Some(75)), TargetStatus::Met);
+        assert_eq!(status(Some(75), Some(74)), TargetStatus::Behind);
+    }
+
+    #[test]
+    fn formats_target() {
+        assert_eq!(TargetHours::from_minutes(450).to_string(), "7h30m");
+        assert_eq!(TargetHours::from_hours(300).to_string(), "300h");
+        assert_eq!(TargetHours::from_minutes(45).to_string(), "45m");
+        assert_eq!(TargetHours::from_minutes(0).to_string(), "0h");
+    }
+}
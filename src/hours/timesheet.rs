@@ -0,0 +1,208 @@
+//! Render monthly timesheet of a client as HTML
+//!
+//! Template placeholders: `{{client}}`, `{{month}}`, `{{generated_at}}`, `{{rows}}`,
+//! `{{project_totals}}`, `{{total_hours}}` and `{{billable_amount}}`.
+use crate::hours::export::format_hours_minutes;
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::strict_string::{ClientName, ProjectName};
+use chrono::{Local, NaiveDate};
+
+/// Template used when there is no template in the settings folder
+pub static DEFAULT_TEMPLATE: &str = include_str!("timesheet.html");
+
+/// Render timesheet of given client's entries with the template
+pub fn render(
+    template: &str,
+    client: &ClientName,
+    month: &NaiveDate,
+    time_entries: &types::TimeEntries,
+    billable_amount: &str,
+) -> String {
+    fill_template(
+        template,
+        &[
+            ("client", escape(client.as_str())),
+            ("month", month.format("%Y-%m").to_string()),
+            ("generated_at", Local::now().format("%Y-%m-%d").to_string()),
+            ("rows", daily_rows(time_entries)),
+            ("project_totals", project_rows(time_entries)),
+            (
+                "total_hours",
+                format_hours_minutes(&time_entries.total_hours()),
+            ),
+            ("billable_amount", escape(billable_amount)),
+        ],
+    )
+}
+
+/// Replace `{{name}}` placeholders in a single pass, so placeholders inside the values are left
+/// as they are. Unknown placeholders are kept in the output
+pub fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find("}}").and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &rest[2..end])
+                .map(|(_, value)| (value, end + 2))
+        });
+        match value {
+            Some((value, length)) => {
+                output.push_str(value);
+                rest = &rest[length..];
+            }
+            None => {
+                output.push_str("{{");
+                rest = &rest[2..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// One row per day and project, with descriptions of the day's entries
+fn daily_rows(time_entries: &types::TimeEntries) -> String {
+    let mut days: Vec<(NaiveDate, ProjectName, Vec<&types::TimeEntry>)> = Vec::new();
+    let mut entries: Vec<&types::TimeEntry> = time_entries.entries.iter().collect();
+    entries.sort_by_key(|entry| entry.start);
+    for entry in entries {
+        let date = entry.start.unwrap().date_naive();
+        match days
+            .iter_mut()
+            .find(|(day, project, _)| day == &date && project == &entry.project)
+        {
+            Some((_, _, day_entries)) => day_entries.push(entry),
+            None => days.push((date, entry.project.clone(), vec![entry])),
+        }
+    }
+    days.sort_by(|a, b| (a.0, &a.1).partial_cmp(&(b.0, &b.1)).unwrap());
+
+    days.iter()
+        .map(|(date, project, entries)| {
+            let mut descriptions: Vec<&str> = Vec::new();
+            for entry in entries {
+                let description = entry.description.as_str();
+                if !description.is_empty() && !descriptions.contains(&description) {
+                    descriptions.push(description);
+                }
+            }
+            let total = types::TimeEntries {
+                entries: entries.iter().map(|entry| (*entry).clone()).collect(),
+            }
+            .total_hours();
+            format!(
+                "    <tr><td>{}</td><td>{}</td><td>{}</td><td class=\"hours\">{}</td></tr>",
+                date.format("%Y-%m-%d"),
+                escape(project.as_str()),
+                escape(&descriptions.join(", ")),
+                format_hours_minutes(&total)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn project_rows(time_entries: &types::TimeEntries) -> String {
+    time_entries
+        .uniq_projects()
+        .iter()
+        .map(|project| {
+            format!(
+                "    <tr><td>{}</td><td class=\"hours\">{}</td></tr>",
+                escape(project.title.as_str()),
+                format_hours_minutes(&project.total_hours())
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Escape text for HTML
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strict_string::Description;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn fills_placeholders_only_from_template() {
+        let values = [
+            ("client", "{{total_hours}}".to_string()),
+            ("total_hours", "7:30".to_string()),
+        ];
+        assert_eq!(
+            fill_template("{{client}}: {{total_hours}} {{unknown}} {{", &values),
+            "{{total_hours}}: 7:30 {{unknown}} {{"
+        );
+    }
+
+    #[test]
+    fn renders_daily_rows_and_totals() {
+        let entry = |day: u32, hour: u32, project: &str, description: &str| {
+            let start = Local.with_ymd_and_hms(2022, 1, day, hour, 0, 0).unwrap();
+            types::TimeEntry {
+                description: Description::new(description),
+                client: Some(ClientName::new("Acme")),
+                project: ProjectName::new(project),
+                start: Some(start),
+                end: Some(start + Duration::minutes(90)),
+                billable_amount_cents: 7550,
+                ..Default::default()
+            }
+        };
+        let time_entries = types::TimeEntries {
+            entries: vec![
+                entry(12, 13, "Website", "Login <form>"),
+                entry(12, 9, "Website", "Planning"),
+                entry(12, 15, "Website", "Planning"),
+                entry(11, 9, "Backend", ""),
+            ],
+        };
+        let template = "{{client}} {{month}}\n{{rows}}\n{{project_totals}}\n{{total_hours}} {{billable_amount}}";
+
+        let html = render(
+            template,
+            &ClientName::new("Acme & Co"),
+            &NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+            &time_entries,
+            "302.00 EUR",
+        );
+
+        assert_eq!(
+            html,
+            "Acme &amp; Co 2022-01
+    <tr><td>2022-01-11</td><td>Backend</td><td></td><td class=\"hours\">1:30</td></tr>
+    <tr><td>2022-01-12</td><td>Website</td><td>Planning, Login &lt;form&gt;</td><td class=\"hours\">4:30</td></tr>
+    <tr><td>Backend</td><td class=\"hours\">1:30</td></tr>
+    <tr><td>Website</td><td class=\"hours\">4:30</td></tr>
+6:00 302.00 EUR"
+        );
+    }
+
+    #[test]
+    fn default_template_has_all_placeholders() {
+        for placeholder in [
+            "{{client}}",
+            "{{month}}",
+            "{{generated_at}}",
+            "{{rows}}",
+            "{{project_totals}}",
+            "{{total_hours}}",
+            "{{billable_amount}}",
+        ] {
+            assert!(DEFAULT_TEMPLATE.contains(placeholder), "{placeholder}");
+        }
+    }
+}
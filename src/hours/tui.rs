@@ -0,0 +1,650 @@
+//! Full-screen dashboard of monthly hours
+//!
+//! The dashboard state and rendering are kept separate from the terminal so that they can be
+//! tested without one. [`run`] only draws the rendered lines and passes key presses back.
+use crate::dates;
+use crate::hours::table::{
+    format_duration, format_project_title, format_target_hours, generate_daily_breakdown_table,
+};
+use crate::hours::target::{TargetHours, TargetStatus};
+use crate::hours::types::{self, TimeEntryCalculations};
+use crate::settings::ProjectConfigs;
+use crate::strict_string::ProjectHash;
+use chrono::{Datelike, Duration, NaiveDate};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Print, Stylize};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::io::Write;
+
+const HELP: &str = "←/→ month  ↑/↓ select  enter entries  d/w/m edit target  r refresh  q quit";
+/// Height of the daily bar chart in lines
+const CHART_HEIGHT: usize = 6;
+const BAR_BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Target which is being edited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// What the dashboard is showing
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Mode {
+    /// Project table and daily chart
+    Projects,
+    /// Entries of the selected project
+    Entries,
+    /// Target of the selected project is being edited
+    EditTarget { period: Period, input: String },
+}
+
+/// Key press results which need something outside of the dashboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    /// Fetch hours again from the integrations and reload the shown month
+    Refresh(NaiveDate),
+    /// Load entries of the month that was switched to
+    LoadMonth(NaiveDate),
+    SaveTarget(ProjectHash, Period, Option<TargetHours>),
+}
+
+impl Command {
+    /// Commands which may fetch from the integrations, which can print or ask the passphrase
+    fn needs_terminal(&self) -> bool {
+        matches!(self, Command::Refresh(_) | Command::LoadMonth(_))
+    }
+}
+
+/// How a rendered line is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Normal,
+    Title,
+    Selected,
+    Dim,
+    Met,
+    Close,
+    Behind,
+}
+
+/// State of the dashboard
+pub struct Dashboard {
+    month: NaiveDate,
+    time_entries: types::TimeEntries,
+    project_configs: ProjectConfigs,
+    projects: Vec<types::Project>,
+    selected: usize,
+    scroll: usize,
+    mode: Mode,
+    message: Option<String>,
+}
+
+impl Dashboard {
+    pub fn new(
+        month: NaiveDate,
+        time_entries: types::TimeEntries,
+        project_configs: ProjectConfigs,
+    ) -> Self {
+        let mut dashboard = Dashboard {
+            month,
+            time_entries: types::TimeEntries { entries: vec![] },
+            project_configs,
+            projects: vec![],
+            selected: 0,
+            scroll: 0,
+            mode: Mode::Projects,
+            message: None,
+        };
+        dashboard.set_time_entries(time_entries);
+        dashboard
+    }
+
+    /// Replace the entries, e.g. after refreshing or switching the month
+    pub fn set_time_entries(&mut self, time_entries: types::TimeEntries) {
+        self.projects = time_entries
+            .uniq_projects()
+            .into_iter()
+            .filter(|project| !self.project_configs.is_archived(&project.key))
+            .collect();
+        self.time_entries = time_entries;
+        self.selected = self.selected.min(self.projects.len().saturating_sub(1));
+        self.scroll = 0;
+        if self.projects.is_empty() {
+            self.mode = Mode::Projects;
+        }
+    }
+
+    pub fn set_project_configs(&mut self, project_configs: ProjectConfigs) {
+        self.project_configs = project_configs;
+    }
+
+    /// Message shown at the bottom until the next key press
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<Command> {
+        self.message = None;
+        match self.mode.clone() {
+            Mode::Projects => self.handle_projects_key(key),
+            Mode::Entries => {
+                match key {
+                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => {
+                        self.mode = Mode::Projects
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => self.scroll += 1,
+                    _ => {}
+                }
+                None
+            }
+            Mode::EditTarget { period, mut input } => match key {
+                KeyCode::Esc => {
+                    self.mode = Mode::Projects;
+                    None
+                }
+                KeyCode::Enter => {
+                    let target = match input.trim() {
+                        "" => None,
+                        input => match input.parse::<TargetHours>() {
+                            Ok(target) => Some(target),
+                            Err(err) => {
+                                self.message = Some(err.to_string());
+                                return None;
+                            }
+                        },
+                    };
+                    self.mode = Mode::Projects;
+                    self.selected_project()
+                        .map(|project| Command::SaveTarget(project.key.clone(), period, target))
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.mode = Mode::EditTarget { period, input };
+                    None
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.mode = Mode::EditTarget { period, input };
+                    None
+                }
+                _ => None,
+            },
+        }
+    }
+
+    fn handle_projects_key(&mut self, key: KeyCode) -> Option<Command> {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Command::Quit),
+            KeyCode::Char('r') => return Some(Command::Refresh(self.month)),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.projects.len() => {
+                self.selected += 1
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.month = dates::month_first_and_last_dates(&self.month.pred_opt().unwrap()).0;
+                return Some(Command::LoadMonth(self.month));
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                let (_, last) = dates::month_first_and_last_dates(&self.month);
+                self.month = last.succ_opt().unwrap();
+                return Some(Command::LoadMonth(self.month));
+            }
+            KeyCode::Enter if self.selected_project().is_some() => {
+                self.scroll = 0;
+                self.mode = Mode::Entries;
+            }
+            KeyCode::Char(c @ ('d' | 'w' | 'm')) => {
+                let period = match c {
+                    'd' => Period::Daily,
+                    'w' => Period::Weekly,
+                    _ => Period::Monthly,
+                };
+                if let Some(project) = self.selected_project() {
+                    let input = self
+                        .project_configs
+                        .get(project)
+                        .and_then(|config| match period {
+                            Period::Daily => config.target_daily_hours,
+                            Period::Weekly => config.target_weekly_hours,
+                            Period::Monthly => config.target_monthly_hours,
+                        })
+                        .map(|target| target.to_string())
+                        .unwrap_or_default();
+                    self.mode = Mode::EditTarget { period, input };
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn selected_project(&self) -> Option<&types::Project> {
+        self.projects.get(self.selected)
+    }
+
+    /// Render the dashboard as lines, at most `height` of them
+    pub fn render(&self, height: usize) -> Vec<(String, Style)> {
+        let mut lines = vec![(
+            format!("My hours {}   {}", self.month.format("%Y-%m"), HELP),
+            Style::Title,
+        )];
+        lines.push((String::new(), Style::Normal));
+        match &self.mode {
+            Mode::Entries => lines.extend(self.render_entries(height.saturating_sub(4))),
+            _ => {
+                lines.extend(self.render_projects());
+                lines.push((String::new(), Style::Normal));
+                lines.extend(self.render_chart());
+            }
+        }
+
+        let footer = match (&self.mode, &self.message) {
+            (_, Some(message)) => Some((message.clone(), Style::Behind)),
+            (Mode::EditTarget { period, input }, None) => Some((
+                format!(
+                    "{:?} target for {} (e.g. 7h30m, empty clears): {}",
+                    period,
+                    self.selected_project()
+                        .map(format_project_title)
+                        .unwrap_or_default(),
+                    input
+                ),
+                Style::Title,
+            )),
+            _ => None,
+        };
+        if let Some(footer) = footer {
+            lines.truncate(height.saturating_sub(2));
+            lines.push((String::new(), Style::Normal));
+            lines.push(footer);
+        }
+        lines.truncate(height);
+        lines
+    }
+
+    fn render_projects(&self) -> Vec<(String, Style)> {
+        if self.projects.is_empty() {
+            return vec![("No hours tracked in this month".to_string(), Style::Dim)];
+        }
+        let titles: Vec<String> = self.projects.iter().map(format_project_title).collect();
+        let width = titles
+            .iter()
+            .map(|title| title.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(7);
+        let row = |title: &str, today: &str, week: &str, month: &str, target: &str| {
+            format!(
+                "  {:width$}  {:>8}  {:>8}  {:>8}  {}",
+                title,
+                today,
+                week,
+                month,
+                target,
+                width = width
+            )
+        };
+
+        let mut lines = vec![(
+            row("Project", "Today", "Week", "Month", "Target"),
+            Style::Dim,
+        )];
+        for (index, (project, title)) in self.projects.iter().zip(titles.iter()).enumerate() {
+            let config = self.project_configs.get(project);
+            let (daily, weekly, monthly) = config
+                .map(|config| {
+                    (
+                        config.target_daily_hours,
+                        config.target_weekly_hours,
+                        config.target_monthly_hours,
+                    )
+                })
+                .unwrap_or((None, None, None));
+            let line = row(
+                title,
+                &format_duration(&project.total_hours_for_current_day()),
+                &format_duration(&project.total_hours_for_current_week()),
+                &format_duration(&project.total_hours()),
+                &format_target_hours(daily, weekly, monthly),
+            );
+            let style = if index == self.selected {
+                Style::Selected
+            } else {
+                status_style(TargetStatus::new(&monthly, &project.total_hours()))
+            };
+            lines.push((line, style));
+        }
+        lines.push((
+            row(
+                "Total",
+                &format_duration(&self.time_entries.total_hours_for_current_day()),
+                &format_duration(&self.time_entries.total_hours_for_current_week()),
+                &format_duration(&self.time_entries.total_hours()),
+                "",
+            ),
+            Style::Title,
+        ));
+        lines
+    }
+
+    /// Vertical bar for each day of the month, scaled to the longest day
+    fn render_chart(&self) -> Vec<(String, Style)> {
+        let (first, last) = dates::month_first_and_last_dates(&self.month);
+        let days: Vec<NaiveDate> = first.iter_days().take_while(|date| date <= &last).collect();
+        let minutes: Vec<i64> = days
+            .iter()
+            .map(|date| daily_hours(&self.time_entries, date).num_minutes())
+            .collect();
+        let max_minutes = minutes.iter().copied().max().unwrap_or(0).max(1);
+
+        let mut lines = vec![(
+            format!(
+                "  Daily hours, longest day {}",
+                format_duration(&Duration::minutes(max_minutes)).trim()
+            ),
+            Style::Dim,
+        )];
+        for level in (0..CHART_HEIGHT).rev() {
+            let line: String = minutes
+                .iter()
+                .map(|minutes| {
+                    let eighths = (minutes * (CHART_HEIGHT as i64) * 8 / max_minutes) as usize;
+                    let block = BAR_BLOCKS[eighths.saturating_sub(level * 8).min(8)];
+                    format!("{} ", block.to_string().repeat(2))
+                })
+                .collect();
+            lines.push((format!("  {}", line.trim_end()), Style::Normal));
+        }
+        let labels: String = days
+            .iter()
+            .map(|date| {
+                if date.day() == 1 || date.day() % 5 == 0 {
+                    format!("{:<3}", date.day())
+                } else {
+                    "   ".to_string()
+                }
+            })
+            .collect();
+        lines.push((format!("  {}", labels.trim_end()), Style::Dim));
+        lines
+    }
+
+    fn render_entries(&self, height: usize) -> Vec<(String, Style)> {
+        let project = match self.selected_project() {
+            Some(project) => project,
+            None => return vec![],
+        };
+        let mut table = generate_daily_breakdown_table(&project.entries);
+        table.force_no_tty();
+        let mut lines = vec![(
+            format!("{}   esc back  ↑/↓ scroll", format_project_title(project)),
+            Style::Title,
+        )];
+        let table = table.to_string();
+        let table_lines: Vec<&str> = table.lines().collect();
+        let scroll = self
+            .scroll
+            .min(table_lines.len().saturating_sub(height.saturating_sub(1)));
+        lines.extend(
+            table_lines
+                .iter()
+                .skip(scroll)
+                .map(|line| (line.to_string(), Style::Normal)),
+        );
+        lines
+    }
+}
+
+fn daily_hours(time_entries: &types::TimeEntries, date: &NaiveDate) -> Duration {
+    types::TimeEntries {
+        entries: time_entries
+            .entries
+            .iter()
+            .filter(|entry| entry.start.is_some_and(|start| &start.date_naive() == date))
+            .cloned()
+            .collect(),
+    }
+    .total_hours()
+}
+
+fn status_style(status: TargetStatus) -> Style {
+    match status {
+        TargetStatus::Met => Style::Met,
+        TargetStatus::Close => Style::Close,
+        TargetStatus::Behind => Style::Behind,
+        TargetStatus::Unset => Style::Normal,
+    }
+}
+
+/// Restores the terminal also when drawing panics
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = leave_screen(&mut std::io::stdout());
+    }
+}
+
+fn enter_screen(stdout: &mut std::io::Stdout) -> std::io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)
+}
+
+fn leave_screen(stdout: &mut std::io::Stdout) -> std::io::Result<()> {
+    let raw_mode = terminal::disable_raw_mode();
+    execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
+    raw_mode
+}
+
+/// Run the dashboard until quit. `execute` is called for commands which need settings or
+/// integrations and may update the dashboard. The dashboard screen is left while fetching, so
+/// that passphrase prompts and messages go to the normal terminal
+pub fn run(
+    dashboard: &mut Dashboard,
+    mut execute: impl FnMut(&mut Dashboard, Command),
+) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    let _guard = TerminalGuard;
+    enter_screen(&mut stdout)?;
+
+    loop {
+        draw(dashboard, &mut stdout)?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key.code,
+            _ => continue,
+        };
+        match dashboard.handle_key(key) {
+            Some(Command::Quit) => return Ok(()),
+            Some(command) if command.needs_terminal() => {
+                leave_screen(&mut stdout)?;
+                execute(dashboard, command);
+                enter_screen(&mut stdout)?;
+            }
+            Some(command) => execute(dashboard, command),
+            None => {}
+        }
+    }
+}
+
+fn draw(dashboard: &Dashboard, stdout: &mut std::io::Stdout) -> std::io::Result<()> {
+    let (width, height) = terminal::size()?;
+    queue!(stdout, terminal::Clear(ClearType::All))?;
+    for (row, (line, style)) in dashboard.render(height as usize).into_iter().enumerate() {
+        let line: String = line.chars().take(width as usize).collect();
+        queue!(stdout, cursor::MoveTo(0, row as u16))?;
+        match style {
+            Style::Normal => queue!(stdout, Print(line))?,
+            Style::Title => queue!(stdout, Print(line.bold()))?,
+            Style::Selected => queue!(stdout, Print(line.reverse()))?,
+            Style::Dim => queue!(stdout, Print(line.dark_grey()))?,
+            Style::Met => queue!(stdout, Print(line.green()))?,
+            Style::Close => queue!(stdout, Print(line.yellow()))?,
+            Style::Behind => queue!(stdout, Print(line.red()))?,
+        }
+    }
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::ProjectConfig;
+    use crate::strict_string::{Description, ProjectName};
+    use chrono::{Local, TimeZone};
+    use std::collections::HashMap;
+
+    fn dashboard() -> Dashboard {
+        let entry = |day: u32, project: &str, minutes: i64| {
+            let start = Local.with_ymd_and_hms(2022, 2, day, 9, 0, 0).unwrap();
+            types::TimeEntry {
+                description: Description::new("Work"),
+                project: ProjectName::new(project),
+                start: Some(start),
+                end: Some(start + Duration::minutes(minutes)),
+                ..Default::default()
+            }
+        };
+        let time_entries = types::TimeEntries {
+            entries: vec![
+                entry(1, "Website", 480),
+                entry(2, "Website", 240),
+                entry(2, "Backend", 120),
+            ],
+        };
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([(
+                entry(1, "Website", 0).project_key(),
+                ProjectConfig {
+                    target_daily_hours: Some(TargetHours::from_hours(6)),
+                    ..Default::default()
+                },
+            )]),
+        };
+        Dashboard::new(
+            NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+            time_entries,
+            project_configs,
+        )
+    }
+
+    #[test]
+    fn selects_projects_and_switches_months() {
+        let mut dashboard = dashboard();
+        assert_eq!(
+            dashboard.selected_project().unwrap().title.as_str(),
+            "Backend"
+        );
+
+        assert_eq!(dashboard.handle_key(KeyCode::Down), None);
+        assert_eq!(dashboard.handle_key(KeyCode::Down), None);
+        assert_eq!(
+            dashboard.selected_project().unwrap().title.as_str(),
+            "Website"
+        );
+
+        assert_eq!(
+            dashboard.handle_key(KeyCode::Left),
+            Some(Command::LoadMonth(
+                NaiveDate::from_ymd_opt(2022, 1, 1).unwrap()
+            ))
+        );
+        assert_eq!(
+            dashboard.handle_key(KeyCode::Right),
+            Some(Command::LoadMonth(
+                NaiveDate::from_ymd_opt(2022, 2, 1).unwrap()
+            ))
+        );
+        assert_eq!(
+            dashboard.handle_key(KeyCode::Char('r')),
+            Some(Command::Refresh(NaiveDate::from_ymd_opt(2022, 2, 1).unwrap()))
+        );
+        assert_eq!(
+            dashboard.handle_key(KeyCode::Char('q')),
+            Some(Command::Quit)
+        );
+    }
+
+    #[test]
+    fn edits_targets_of_selected_project() {
+        let mut dashboard = dashboard();
+        dashboard.handle_key(KeyCode::Down);
+        dashboard.handle_key(KeyCode::Char('d'));
+        assert_eq!(
+            dashboard.mode,
+            Mode::EditTarget {
+                period: Period::Daily,
+                input: "6h".to_string()
+            }
+        );
+
+        dashboard.handle_key(KeyCode::Backspace);
+        dashboard.handle_key(KeyCode::Backspace);
+        dashboard.handle_key(KeyCode::Char('x'));
+        assert_eq!(dashboard.handle_key(KeyCode::Enter), None);
+        assert!(dashboard.message.is_some());
+
+        dashboard.handle_key(KeyCode::Backspace);
+        for c in "7h30m".chars() {
+            dashboard.handle_key(KeyCode::Char(c));
+        }
+        let key = dashboard.selected_project().unwrap().key.clone();
+        assert_eq!(
+            dashboard.handle_key(KeyCode::Enter),
+            Some(Command::SaveTarget(
+                key,
+                Period::Daily,
+                Some(TargetHours::from_minutes(450))
+            ))
+        );
+        assert_eq!(dashboard.mode, Mode::Projects);
+    }
+
+    #[test]
+    fn renders_projects_and_daily_chart() {
+        let lines: Vec<String> = dashboard()
+            .render(40)
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+
+        assert_eq!(
+            lines[2..],
+            [
+                "  Project     Today      Week     Month  Target",
+                "  Backend                        2h  0m  ",
+                "  Website                       12h  0m  6h / - / -",
+                "  Total                         14h  0m  ",
+                "",
+                "  Daily hours, longest day 8h  0m",
+                "  ██",
+                "  ██ ▄▄",
+                "  ██ ██",
+                "  ██ ██",
+                "  ██ ██",
+                "  ██ ██",
+                "  1           5              10             15             20             25",
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_entries_of_selected_project() {
+        let mut dashboard = dashboard();
+        dashboard.handle_key(KeyCode::Down);
+        dashboard.handle_key(KeyCode::Enter);
+
+        let lines = dashboard.render(40);
+
+        assert_eq!(lines[2].0, "Website   esc back  ↑/↓ scroll");
+        assert!(lines
+            .iter()
+            .any(|(line, _)| line.contains("2022-02-01 (Tue)")));
+        dashboard.handle_key(KeyCode::Esc);
+        assert_eq!(dashboard.mode, Mode::Projects);
+    }
+}
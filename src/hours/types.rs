@@ -1,8 +1,10 @@
+use crate::hours::rounding::{Rounding, RoundingScope};
 use crate::strict_string::{ClientName, Description, ProjectHash, ProjectName};
 use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashSet;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::fmt;
 use std::str;
 
 pub trait TimeEntryCalculations {
@@ -16,17 +18,24 @@ pub trait TimeEntryCalculations {
         Self::sum(&durations)
     }
     fn total_hours_for_current_day(&self) -> Duration {
+        self.total_hours_for_day(&Local::now().date_naive())
+    }
+    fn total_hours_for_day(&self, date: &NaiveDate) -> Duration {
         let durations: Vec<Duration> = self
             .entries()
             .iter()
-            .filter(|entry| entry.is_for_current_date())
+            .filter(|entry| &entry.start.unwrap().date_naive() == date)
             .map(|entry| entry.duration())
             .collect();
         Self::sum(&durations)
     }
 
     fn total_hours_for_current_week(&self) -> Duration {
-        let dates = self.dates_from_monday();
+        self.total_hours_for_week_of(&Local::now().date_naive())
+    }
+    /// Hours from Monday until the given day
+    fn total_hours_for_week_of(&self, date: &NaiveDate) -> Duration {
+        let dates = self.dates_from_monday(date);
         let durations: Vec<Duration> = self
             .entries()
             .iter()
@@ -37,9 +46,12 @@ pub trait TimeEntryCalculations {
     }
 
     fn daily_avg_for_current_week(&self) -> Duration {
-        let working_days = self.current_week_work_days().len() as i64;
+        self.daily_avg_for_week_of(&Local::now().date_naive())
+    }
+    fn daily_avg_for_week_of(&self, date: &NaiveDate) -> Duration {
+        let working_days = self.week_work_days(date).len() as i64;
         if working_days > 0 {
-            let total_minutes = self.total_hours_for_current_week().num_minutes();
+            let total_minutes = self.total_hours_for_week_of(date).num_minutes();
             let minutes_per_day = total_minutes / working_days;
             Duration::minutes(minutes_per_day)
         } else {
@@ -58,16 +70,110 @@ pub trait TimeEntryCalculations {
         }
     }
 
+    /// Tracked hours of entries marked as billable in the integration
+    fn billable_hours(&self) -> Duration {
+        let durations: Vec<Duration> = self
+            .entries()
+            .iter()
+            .filter(|entry| entry.billable == Some(true))
+            .map(|entry| entry.duration())
+            .collect();
+        Self::sum(&durations)
+    }
+
+    fn non_billable_hours(&self) -> Duration {
+        let durations: Vec<Duration> = self
+            .entries()
+            .iter()
+            .filter(|entry| entry.billable != Some(true))
+            .map(|entry| entry.duration())
+            .collect();
+        Self::sum(&durations)
+    }
+
+    /// Share of billable hours from all hours in percents, `None` when nothing is tracked
+    fn billable_percent(&self) -> Option<i64> {
+        let total_minutes = self.total_hours().num_minutes();
+        if total_minutes > 0 {
+            Some(self.billable_hours().num_minutes() * 100 / total_minutes)
+        } else {
+            None
+        }
+    }
+
+    /// Total hours with the rounding rules, e.g. each entry rounded up to 15 minutes. Entries
+    /// without start or end have no duration in any scope, running timers should be given an end
+    /// with `with_running_until` first
+    fn rounded_hours(&self, rounding: &Rounding) -> Duration {
+        let timed_entries: Vec<(NaiveDate, Duration)> = self
+            .entries()
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .start
+                    .zip(entry.end)
+                    .map(|(start, end)| (start.date_naive(), end.signed_duration_since(start)))
+            })
+            .collect();
+        let durations: Vec<Duration> = match rounding.per {
+            RoundingScope::Entry => timed_entries
+                .iter()
+                .map(|(_, duration)| rounding.apply(duration))
+                .collect(),
+            RoundingScope::Day => {
+                let mut days: HashMap<NaiveDate, Vec<Duration>> = HashMap::new();
+                for (date, duration) in timed_entries {
+                    days.entry(date).or_default().push(duration);
+                }
+                days.values()
+                    .map(|durations| rounding.apply(&Self::sum(durations)))
+                    .collect()
+            }
+            RoundingScope::Total => {
+                let durations: Vec<Duration> =
+                    timed_entries.iter().map(|(_, duration)| *duration).collect();
+                vec![rounding.apply(&Self::sum(&durations))]
+            }
+        };
+        Self::sum(&durations)
+    }
+
+    /// Billable amount in cents. Entries without amount from the integration are billed with the
+    /// hourly rate, using rounded hours when rounding is given. Entries marked as non-billable are
+    /// not billed with the rate
+    fn billable_amount_cents(&self, hourly_rate: Option<f64>, rounding: Option<&Rounding>) -> i64 {
+        let (with_amount, without_amount): (Vec<TimeEntry>, Vec<TimeEntry>) = self
+            .entries()
+            .iter()
+            .filter(|entry| entry.billable_amount_cents > 0 || entry.billable != Some(false))
+            .cloned()
+            .partition(|entry| entry.billable_amount_cents > 0);
+        let amounts: i64 = with_amount
+            .iter()
+            .map(|entry| entry.billable_amount_cents as i64)
+            .sum();
+        let without_amount = TimeEntries {
+            entries: without_amount,
+        };
+        let hours = match rounding {
+            Some(rounding) => without_amount.rounded_hours(rounding),
+            None => without_amount.total_hours(),
+        };
+        let rated: i64 = hourly_rate
+            .map(|rate| (hours.num_seconds() as f64 * rate * 100.0 / 3600.0).round() as i64)
+            .unwrap_or(0);
+        amounts + rated
+    }
+
     fn sum(durations: &[Duration]) -> Duration {
-        return durations
+        durations
             .iter()
             .fold(Duration::minutes(0), |total_dur, entry| {
                 total_dur.checked_add(entry).unwrap()
-            });
+            })
     }
 
-    fn dates_from_monday(&self) -> Vec<NaiveDate> {
-        let current_date = Local::now().date_naive();
+    fn dates_from_monday(&self, current_date: &NaiveDate) -> Vec<NaiveDate> {
         let monday = NaiveDate::from_isoywd_opt(
             current_date.year(),
             current_date.iso_week().week(),
@@ -80,8 +186,8 @@ pub trait TimeEntryCalculations {
             .collect()
     }
 
-    fn current_week_work_days(&self) -> HashSet<NaiveDate> {
-        let dates_from_monday = self.dates_from_monday();
+    fn week_work_days(&self, date: &NaiveDate) -> HashSet<NaiveDate> {
+        let dates_from_monday = self.dates_from_monday(date);
         let mut working_dates = HashSet::new();
         for entry in self
             .entries()
@@ -104,7 +210,7 @@ pub trait TimeEntryCalculations {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Eq, PartialEq, Hash)]
 pub struct TimeEntry {
     pub description: Description,
     pub client: Option<ClientName>,
@@ -112,18 +218,71 @@ pub struct TimeEntry {
     pub start: Option<DateTime<Local>>,
     pub end: Option<DateTime<Local>>,
     pub billable_amount_cents: usize,
+    /// Whether the entry is marked as billable. Missing for entries stored by older versions
+    #[serde(default)]
+    pub billable: Option<bool>,
+    /// Tags of the entry. Missing for entries stored by older versions
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Name of the task of the entry
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Where the project comes from. Missing for entries stored by older versions
+    #[serde(default)]
+    pub project_source: Option<ProjectSource>,
+}
+
+/// Integration where time entries are fetched from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Toggl,
+}
+
+/// Identity of a project in the integration, stays the same when project is renamed
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+pub struct ProjectSource {
+    pub provider: Provider,
+    pub workspace_id: usize,
+    pub project_id: usize,
 }
 
 impl TimeEntry {
     pub fn duration(&self) -> Duration {
         self.end.unwrap().signed_duration_since(self.start.unwrap())
     }
-    pub fn is_for_current_date(&self) -> bool {
-        self.start.unwrap().date_naive() == Local::now().date_naive()
+    /// Key for the project. Uses the project's id in the integration when it's known and falls back to the project name
+    pub fn project_key(&self) -> ProjectHash {
+        match &self.project_source {
+            Some(source) => hash_key(&format!(
+                "{}:{}:{}",
+                source.provider, source.workspace_id, source.project_id
+            )),
+            None => self.legacy_project_key(),
+        }
+    }
+    /// Key for the project based only on the project name, used before projects had ids
+    pub fn legacy_project_key(&self) -> ProjectHash {
+        hash_key(self.project.as_str())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provider::Toggl => write!(f, "toggl"),
+        }
+    }
+}
+
+fn hash_key(input: &str) -> ProjectHash {
+    let mut hasher = Sha256::new();
+    hasher.update(input);
+    let finalized_hash = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    ProjectHash::new(finalized_hash)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimeEntries {
     pub entries: Vec<TimeEntry>,
 }
@@ -133,33 +292,69 @@ impl TimeEntryCalculations for TimeEntries {
     }
 }
 impl TimeEntries {
+    /// Entries with running timers ending at `now`. Only for showing hours, running timers
+    /// aren't billed or exported
+    pub fn with_running_until(self, now: DateTime<Local>) -> TimeEntries {
+        TimeEntries {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|entry| TimeEntry {
+                    end: entry.end.or(Some(now)),
+                    ..entry
+                })
+                .collect(),
+        }
+    }
     pub fn uniq_projects(&self) -> Vec<Project> {
-        let mut projects = HashSet::new();
+        let mut projects: HashMap<ProjectHash, Project> = HashMap::new();
 
         for entry in self.entries.iter() {
-            let mut hasher = Sha256::new();
-            hasher.update(entry.project.as_str());
-            let finalized_hash = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect::<String>();
-            let project = Project {
-                title: entry.project.clone(),
-                client: entry.client.clone(),
-                key: ProjectHash::new(finalized_hash),
-                entries: self.entries_for_project(&entry.project),
-            };
-            projects.insert(project);
+            let key = entry.project_key();
+            match projects.entry(key.clone()) {
+                Entry::Occupied(mut occupied) => occupied.get_mut().entries.push(entry.clone()),
+                Entry::Vacant(vacant) => {
+                    vacant.insert(Project {
+                        title: entry.project.clone(),
+                        client: entry.client.clone(),
+                        entries: vec![entry.clone()],
+                        key,
+                    });
+                }
+            }
         }
-        let mut projects_as_vec: Vec<Project> = projects.iter().cloned().collect();
-        projects_as_vec.sort_by(|a, b| a.title.partial_cmp(&b.title).unwrap());
+        let mut projects_as_vec: Vec<Project> = projects.into_values().collect();
+        projects_as_vec.sort_by(|a, b| {
+            (&a.title, &a.client, &a.key)
+                .partial_cmp(&(&b.title, &b.client, &b.key))
+                .unwrap()
+        });
         projects_as_vec
     }
 
-    fn entries_for_project(&self, project_title: &ProjectName) -> Vec<TimeEntry> {
-        return self
-            .entries
-            .iter()
-            .filter(|entry| entry.project.eq(project_title))
-            .cloned()
-            .collect();
+    pub fn uniq_clients(&self) -> Vec<Client> {
+        let mut clients: Vec<Client> = Vec::new();
+
+        for project in self.uniq_projects() {
+            match clients.iter_mut().find(|client| client.name == project.client) {
+                Some(client) => {
+                    client.entries.extend(project.entries.iter().cloned());
+                    client.projects.push(project);
+                }
+                None => clients.push(Client {
+                    name: project.client.clone(),
+                    entries: project.entries.clone(),
+                    projects: vec![project],
+                }),
+            }
+        }
+        clients.sort_by(|a, b| match (&a.name, &b.name) {
+            (Some(a), Some(b)) => a.partial_cmp(b).unwrap(),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        clients
     }
 }
 
@@ -171,12 +366,32 @@ pub struct Project {
     pub entries: Vec<TimeEntry>,
 }
 
+impl Project {
+    /// Key for the project based only on the project name, used before projects had ids
+    pub fn legacy_key(&self) -> ProjectHash {
+        hash_key(self.title.as_str())
+    }
+}
+
 impl TimeEntryCalculations for Project {
     fn entries(&self) -> &Vec<TimeEntry> {
         &self.entries
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub name: Option<ClientName>,
+    pub projects: Vec<Project>,
+    pub entries: Vec<TimeEntry>,
+}
+
+impl TimeEntryCalculations for Client {
+    fn entries(&self) -> &Vec<TimeEntry> {
+        &self.entries
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,4 +408,181 @@ mod tests {
             "985959785319747668373cc6dee294b11db782b03cdd90a2851fbdc0637c6b7b"
         );
     }
+
+    #[test]
+    fn rounds_entries_days_or_total() {
+        use crate::hours::rounding::RoundingDirection;
+        use chrono::TimeZone;
+
+        let entry = |day: u32, hour: u32, minutes: i64| {
+            let start = Local.with_ymd_and_hms(2022, 1, day, hour, 0, 0).unwrap();
+            TimeEntry {
+                project: ProjectName::new("Website"),
+                start: Some(start),
+                end: Some(start + Duration::minutes(minutes)),
+                ..Default::default()
+            }
+        };
+        let time_entries = TimeEntries {
+            entries: vec![entry(11, 9, 5), entry(11, 13, 5), entry(12, 9, 20)],
+        };
+        let rounding = |per: RoundingScope| Rounding {
+            increment_minutes: 15,
+            direction: RoundingDirection::Up,
+            per,
+        };
+
+        assert_eq!(time_entries.total_hours(), Duration::minutes(30));
+        assert_eq!(
+            time_entries.rounded_hours(&rounding(RoundingScope::Entry)),
+            Duration::minutes(60)
+        );
+        assert_eq!(
+            time_entries.rounded_hours(&rounding(RoundingScope::Day)),
+            Duration::minutes(45)
+        );
+        let without_start = TimeEntries {
+            entries: vec![TimeEntry::default(), entry(12, 9, 20)],
+        };
+        for per in [RoundingScope::Entry, RoundingScope::Day, RoundingScope::Total] {
+            assert_eq!(without_start.rounded_hours(&rounding(per)), Duration::minutes(30));
+        }
+        assert_eq!(
+            time_entries.rounded_hours(&rounding(RoundingScope::Total)),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            time_entries.billable_amount_cents(Some(60.0), Some(&rounding(RoundingScope::Entry))),
+            6000
+        );
+        assert_eq!(time_entries.billable_amount_cents(Some(60.0), None), 3000);
+        let non_billable = TimeEntries {
+            entries: vec![
+                TimeEntry {
+                    billable: Some(false),
+                    ..entry(12, 9, 30)
+                },
+                TimeEntry {
+                    billable: Some(false),
+                    billable_amount_cents: 1000,
+                    ..entry(12, 13, 30)
+                },
+                TimeEntry {
+                    billable: Some(true),
+                    ..entry(12, 15, 30)
+                },
+            ],
+        };
+        assert_eq!(non_billable.billable_amount_cents(Some(60.0), None), 4000);
+    }
+
+    #[test]
+    fn counts_running_timer_until_given_time() {
+        use chrono::TimeZone;
+
+        let start = Local.with_ymd_and_hms(2022, 1, 12, 9, 0, 0).unwrap();
+        let time_entries = TimeEntries {
+            entries: vec![
+                TimeEntry {
+                    start: Some(start),
+                    end: Some(start + Duration::minutes(30)),
+                    ..Default::default()
+                },
+                TimeEntry {
+                    start: Some(start),
+                    end: None,
+                    ..Default::default()
+                },
+            ],
+        }
+        .with_running_until(start + Duration::minutes(90));
+
+        assert_eq!(time_entries.entries[0].duration().num_minutes(), 30);
+        assert_eq!(time_entries.entries[1].duration().num_minutes(), 90);
+    }
+
+    #[test]
+    fn groups_projects_by_client() {
+        let entry = |client: Option<&str>, project: &str| TimeEntry {
+            description: Description::new("Description"),
+            client: client.map(ClientName::new),
+            project: ProjectName::new(project),
+            start: Some(Local::now()),
+            end: Some(Local::now() + Duration::minutes(30)),
+            ..Default::default()
+        };
+        let time_entries = TimeEntries {
+            entries: vec![
+                entry(None, "Internal"),
+                entry(Some("Beta"), "Landing page"),
+                entry(Some("Acme"), "Website"),
+                entry(Some("Acme"), "Maintenance"),
+                entry(Some("Acme"), "Maintenance"),
+            ],
+        };
+
+        let clients = time_entries.uniq_clients();
+
+        let names: Vec<Option<ClientName>> = clients.iter().map(|c| c.name.clone()).collect();
+        assert_eq!(
+            names,
+            vec![
+                Some(ClientName::new("Acme")),
+                Some(ClientName::new("Beta")),
+                None
+            ]
+        );
+        assert_eq!(clients[0].projects.len(), 2);
+        assert_eq!(clients[0].entries.len(), 3);
+        assert_eq!(clients[0].total_hours().num_minutes(), 90);
+    }
+
+    #[test]
+    fn projects_are_identified_by_source() {
+        let entry = |client: &str, project: &str, project_id: usize| TimeEntry {
+            description: Description::new("Description"),
+            client: Some(ClientName::new(client)),
+            project: ProjectName::new(project),
+            start: Some(Local::now()),
+            end: Some(Local::now() + Duration::minutes(30)),
+            project_source: Some(ProjectSource {
+                provider: Provider::Toggl,
+                workspace_id: 1,
+                project_id,
+            }),
+            ..Default::default()
+        };
+        let time_entries = TimeEntries {
+            entries: vec![
+                entry("Acme", "Maintenance", 1),
+                entry("Beta", "Maintenance", 2),
+                entry("Beta", "Maintenance", 2),
+            ],
+        };
+
+        let projects = time_entries.uniq_projects();
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].client, Some(ClientName::new("Acme")));
+        assert_eq!(projects[0].entries.len(), 1);
+        assert_eq!(projects[1].entries.len(), 2);
+        assert_ne!(projects[0].key, projects[1].key);
+        assert_eq!(
+            time_entries.entries[0].legacy_project_key(),
+            time_entries.entries[1].legacy_project_key()
+        );
+    }
+
+    #[test]
+    fn project_key_falls_back_to_project_name() {
+        let entry = TimeEntry {
+            project: ProjectName::new("Project"),
+            ..Default::default()
+        };
+        assert_eq!(entry.project_key(), entry.legacy_project_key());
+        assert_eq!(
+            entry.project_key().as_str(),
+            "985959785319747668373cc6dee294b11db782b03cdd90a2851fbdc0637c6b7b"
+        );
+    }
 }
@@ -1,15 +1,178 @@
-use read_input::prelude::*;
-pub fn ask_input<T: std::str::FromStr>(question: &str) -> Option<T> {
-    println!("{}", question);
-    let user_input = input::<String>().get();
-    if user_input.trim().is_empty() {
-        return None;
-    }
-    match user_input.parse::<T>() {
-        Ok(value) => Some(value),
-        Err(_err) => {
-            println!("Error occured while parsing value '{}'", user_input);
-            std::process::exit(1);
+use comfy_table::{ContentArrangement, Table};
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+static INPUT_DISABLED: AtomicBool = AtomicBool::new(false);
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// When output is colored
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colors when printing to a terminal and `NO_COLOR` is not set
+    Auto,
+    /// Always colors, also when piped
+    Always,
+    /// Never colors. Target status is shown with symbols instead
+    Never,
+}
+
+/// Errors when asking input from user
+#[derive(Debug)]
+pub enum InputError {
+    /// Input was asked while running in non-interactive mode
+    NotInteractive,
+    /// Input stream was closed before an answer was given
+    Closed,
+    /// Reading input failed
+    Io(io::Error),
+    /// Answer couldn't be parsed
+    Invalid(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::NotInteractive => write!(f, "input is not available in non-interactive mode"),
+            InputError::Closed => write!(f, "input was closed"),
+            InputError::Io(err) => write!(f, "couldn't read input: {}", err),
+            InputError::Invalid(value) => write!(f, "couldn't parse value '{}'", value),
+        }
+    }
+}
+
+/// Never prompt anything from user during this run
+pub fn disable_input() {
+    INPUT_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Returns true if user can be prompted, i.e. input is not disabled and stdin is a terminal
+pub fn is_interactive() -> bool {
+    input_enabled(INPUT_DISABLED.load(Ordering::Relaxed), io::stdin().is_terminal())
+}
+
+fn input_enabled(input_disabled: bool, is_terminal: bool) -> bool {
+    !input_disabled && is_terminal
+}
+
+/// Choose when output is colored during this run
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Returns true if output should be colored
+pub fn use_colors() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    colors_enabled(
+        COLOR_CHOICE.load(Ordering::Relaxed),
+        no_color,
+        io::stdout().is_terminal(),
+    )
+}
+
+fn colors_enabled(choice: u8, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        choice if choice == ColorChoice::Always as u8 => true,
+        choice if choice == ColorChoice::Never as u8 => false,
+        _ => !no_color && is_terminal,
+    }
+}
+
+/// Style table for printing to stdout: colors by the color choice and rows wrapped to the width
+/// of the terminal
+pub fn style_table(table: &mut Table) {
+    if use_colors() {
+        table.enforce_styling();
+    } else {
+        table.force_no_tty();
+    }
+    if io::stdout().is_terminal() {
+        if let Ok((width, _)) = crossterm::terminal::size() {
+            table
+                .set_width(width)
+                .set_content_arrangement(ContentArrangement::Dynamic);
         }
     }
 }
+
+/// Ask yes or no question, no by default. Answer is always no when input is not available
+pub fn confirm(question: &str) -> bool {
+    matches!(
+        ask_input::<String>(&format!("{} [y/N]", question)),
+        Ok(Some(answer)) if answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes")
+    )
+}
+
+/// Ask input from user and parse it. Empty answer is returned as `None`
+pub fn ask_input<T: std::str::FromStr>(question: &str) -> Result<Option<T>, InputError> {
+    if !is_interactive() {
+        return Err(InputError::NotInteractive);
+    }
+    println!("{}", question);
+    let mut user_input = String::new();
+    match io::stdin().lock().read_line(&mut user_input) {
+        Ok(0) => Err(InputError::Closed),
+        Ok(_) => parse_input(&user_input),
+        Err(err) => Err(InputError::Io(err)),
+    }
+}
+
+/// Ask input without echoing it, e.g. passphrase. The question is written to the terminal, so
+/// that it isn't mixed with the output
+pub fn ask_secret(question: &str) -> Result<Option<String>, InputError> {
+    if !is_interactive() {
+        return Err(InputError::NotInteractive);
+    }
+    match rpassword::prompt_password(format!("{} ", question)) {
+        Ok(answer) if answer.is_empty() => Ok(None),
+        Ok(answer) => Ok(Some(answer)),
+        Err(err) => Err(InputError::Io(err)),
+    }
+}
+
+fn parse_input<T: std::str::FromStr>(user_input: &str) -> Result<Option<T>, InputError> {
+    let user_input = user_input.trim();
+    if user_input.is_empty() {
+        return Ok(None);
+    }
+    user_input
+        .parse::<T>()
+        .map(Some)
+        .map_err(|_err| InputError::Invalid(user_input.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_input() {
+        assert_eq!(parse_input::<u8>(" 8\n").unwrap(), Some(8));
+        assert_eq!(parse_input::<u8>("\n").unwrap(), None);
+        assert!(matches!(
+            parse_input::<u8>("eight\n"),
+            Err(InputError::Invalid(value)) if value == "eight"
+        ));
+    }
+
+    #[test]
+    fn colors_follow_choice_no_color_and_terminal() {
+        let (auto, always, never) = (
+            ColorChoice::Auto as u8,
+            ColorChoice::Always as u8,
+            ColorChoice::Never as u8,
+        );
+        assert!(colors_enabled(auto, false, true));
+        assert!(!colors_enabled(auto, true, true));
+        assert!(!colors_enabled(auto, false, false));
+        assert!(colors_enabled(always, true, false));
+        assert!(!colors_enabled(never, false, true));
+    }
+
+    #[test]
+    fn input_is_asked_only_from_terminal_when_enabled() {
+        assert!(input_enabled(false, true));
+        assert!(!input_enabled(true, true));
+        assert!(!input_enabled(false, false));
+    }
+}
@@ -2,9 +2,10 @@ use crate::dates;
 use crate::hours::types::{TimeEntries, TimeEntry};
 use crate::integrations::toggl::Config as TogglConfig;
 use crate::settings;
-use chrono::Local;
+use chrono::{Local, NaiveDate};
 use clap::Parser;
 
+pub mod secrets;
 pub mod toggl;
 
 #[derive(Parser, Debug)]
@@ -21,13 +22,16 @@ pub enum Action {
 #[derive(Parser, Debug, Clone)]
 pub enum Integration {
     #[clap(name = "toggl")]
-    TogglIntegration,
+    TogglIntegration {
+        #[clap(flatten)]
+        key: secrets::KeyOptions,
+    },
 }
 
 pub fn execute(action: &Action) {
     match action {
         Action::Setup { integration } => match integration {
-            Integration::TogglIntegration => toggl::setup(),
+            Integration::TogglIntegration { key } => toggl::setup(key),
         },
         Action::List => {
             let settings = settings::load();
@@ -37,21 +41,29 @@ pub fn execute(action: &Action) {
 }
 
 /// Loop over integrations and get time entries for current month
-pub fn get_monthly_time_entries() -> TimeEntries {
-    let settings = settings::load();
+pub fn get_monthly_time_entries() -> Result<TimeEntries, String> {
     let (start_date, end_date) = dates::month_first_and_last_dates(&Local::now().date_naive());
+    get_time_entries_for_dates(&start_date, &end_date)
+}
+
+/// Loop over integrations and get time entries between given dates
+pub fn get_time_entries_for_dates(
+    start_date: &NaiveDate,
+    end_date: &NaiveDate,
+) -> Result<TimeEntries, String> {
+    let settings = settings::try_load().map_err(|err| err.to_string())?;
 
     let entries: Vec<Vec<TimeEntry>> = match settings.toggl {
         Some(toggl) => toggl
             .iter()
-            .map(|toggl_config| toggl::time_entries_for_dates(toggl_config, &start_date, &end_date))
-            .collect(),
+            .map(|toggl_config| toggl::time_entries_for_dates(toggl_config, start_date, end_date))
+            .collect::<Result<_, String>>()?,
         None => Vec::new(),
     };
 
-    TimeEntries {
+    Ok(TimeEntries {
         entries: entries.concat(),
-    }
+    })
 }
 
 /// List integrations to given writer
@@ -81,12 +93,22 @@ mod tests {
     mod show_integrations {
         use super::super::*;
         use crate::integrations;
+        use crate::integrations::secrets::KeySource;
         use crate::strict_string::{ApiKey, Email, Fullname, WorkspaceName};
 
         static DEFAULT_CONFIG: settings::Config = settings::Config {
+            schema_version: None,
             refresh_treshold: Some(180),
             refreshed_at: None,
             project_configs: None,
+            client_configs: None,
+            no_input: None,
+            currency: None,
+            target_billable_percent: None,
+            invoice: None,
+            target_daily_hours: None,
+            holidays: None,
+            table: None,
             toggl: None,
         };
         #[test]
@@ -103,7 +125,7 @@ mod tests {
             let config = settings::Config {
                 toggl: Some(
                     [TogglConfig {
-                        key: ApiKey::new("key".to_string()),
+                        key: KeySource::Plain(ApiKey::new("key".to_string())),
                         user: integrations::toggl::User {
                             id: 1,
                             fullname: Fullname::new("John Doe".to_string()),
@@ -120,6 +142,15 @@ mod tests {
                 refresh_treshold: Some(180),
                 refreshed_at: None,
                 project_configs: None,
+                client_configs: None,
+                no_input: None,
+                currency: None,
+                target_billable_percent: None,
+                invoice: None,
+                target_daily_hours: None,
+                holidays: None,
+                table: None,
+                schema_version: None,
             };
 
             list_integrations(&config, &mut result);
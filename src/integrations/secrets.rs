@@ -0,0 +1,308 @@
+//! API keys stored outside the settings file
+//!
+//! Keys can be read from an environment variable, from the output of a command, e.g.
+//! `pass show toggl`, or from the passphrase-encrypted secrets file in the settings folder. Keys
+//! are resolved only when hours are fetched. Without input, e.g. from cron, the passphrase is read
+//! from [`PASSPHRASE_ENV`].
+use crate::hours::ui;
+use crate::settings;
+use crate::strict_string::ApiKey;
+use age::secrecy::SecretString;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+static SECRETS_FILENAME: &str = "secrets.age";
+/// Environment variable for the passphrase of the secrets file
+pub static PASSPHRASE_ENV: &str = "MY_HOURS_PASSPHRASE";
+
+#[derive(Parser, Debug)]
+pub enum Action {
+    /// Store secret, e.g. API key, in the encrypted secrets file
+    Set {
+        /// Name of the secret, e.g. toggl
+        name: String,
+    },
+    /// Remove secret from the secrets file
+    Remove {
+        /// Name of the secret
+        name: String,
+    },
+    /// List names of the stored secrets
+    List,
+}
+
+/// Where API key of an integration is read from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum KeySource {
+    /// Key stored in the settings file as is
+    Plain(ApiKey),
+    /// Name of the environment variable holding the key
+    Env { env: String },
+    /// Command printing the key on the first line of its output
+    Command { command: String },
+    /// Name of the secret in the secrets file
+    Secret { secret: String },
+}
+
+/// Options for storing API key outside the settings file during setup
+#[derive(clap::Args, Debug, Clone, Default)]
+#[group(multiple = false)]
+pub struct KeyOptions {
+    /// Read API key from environment variable instead of storing it in settings
+    #[clap(long, value_name = "VARIABLE")]
+    key_env: Option<String>,
+    /// Read API key from output of command, e.g. "pass show toggl"
+    #[clap(long, value_name = "COMMAND")]
+    key_command: Option<String>,
+    /// Read API key from the encrypted secrets file, see `secrets set`
+    #[clap(long, value_name = "NAME")]
+    key_secret: Option<String>,
+}
+
+impl KeyOptions {
+    /// Source of the key, if any option was given
+    pub fn source(&self) -> Option<KeySource> {
+        if let Some(env) = &self.key_env {
+            Some(KeySource::Env { env: env.clone() })
+        } else if let Some(command) = &self.key_command {
+            Some(KeySource::Command {
+                command: command.clone(),
+            })
+        } else {
+            self.key_secret.as_ref().map(|secret| KeySource::Secret {
+                secret: secret.clone(),
+            })
+        }
+    }
+}
+
+impl KeySource {
+    /// Read the key from its source
+    pub fn resolve(&self) -> Result<ApiKey, String> {
+        let key = match self {
+            KeySource::Plain(key) => return Ok(key.clone()),
+            KeySource::Env { env } => std::env::var(env)
+                .map_err(|_| format!("Environment variable {} for API key is not set", env))?,
+            KeySource::Command { command } => run_command(command)?,
+            KeySource::Secret { secret } => {
+                let path = secrets_path()?;
+                let secrets = read_secrets(&path, &ask_passphrase(&path)?)?;
+                secrets.get(secret).cloned().ok_or_else(|| {
+                    format!(
+                        "Secret '{}' not found. Add it with `my-hours secrets set {}`",
+                        secret, secret
+                    )
+                })?
+            }
+        };
+        match key.trim() {
+            "" => Err("API key is empty".to_string()),
+            key => Ok(ApiKey::new(key.to_string())),
+        }
+    }
+}
+
+pub fn execute(action: &Action) {
+    if let Err(err) = try_execute(action) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn try_execute(action: &Action) -> Result<(), String> {
+    let path = secrets_path()?;
+    let exists = Path::new(&path).exists();
+    let passphrase = match action {
+        Action::List | Action::Remove { .. } if !exists => {
+            return Err("No secrets stored yet".to_string())
+        }
+        _ if exists => ask_passphrase(&path)?,
+        _ => ask_new_passphrase()?,
+    };
+    let mut secrets = if exists {
+        read_secrets(&path, &passphrase)?
+    } else {
+        BTreeMap::new()
+    };
+    match action {
+        Action::List => {
+            for name in secrets.keys() {
+                println!("{}", name);
+            }
+            return Ok(());
+        }
+        Action::Set { name } => {
+            let value = ask_secret(&format!("Value of {}:", name))?;
+            secrets.insert(name.clone(), value);
+        }
+        Action::Remove { name } => {
+            if secrets.remove(name).is_none() {
+                return Err(format!("Secret '{}' not found", name));
+            }
+        }
+    }
+    write_secrets(&path, &passphrase, &secrets)?;
+    eprintln!("Secrets saved to {}", path);
+    Ok(())
+}
+
+fn secrets_path() -> Result<String, String> {
+    settings::app_file_path(SECRETS_FILENAME).map_err(|err| err.to_string())
+}
+
+fn run_command(command: &str) -> Result<String, String> {
+    let output = if cfg!(windows) {
+        process::Command::new("cmd").args(["/C", command]).output()
+    } else {
+        process::Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|err| format!("Couldn't run '{}': {}", command, err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Command '{}' failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or("").to_string())
+}
+
+fn ask_passphrase(path: &str) -> Result<String, String> {
+    match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) if !ui::is_interactive() => Err(format!(
+            "Passphrase for {} can't be asked without input, set {}",
+            path, PASSPHRASE_ENV
+        )),
+        Err(_) => ask_secret(&format!("Passphrase for {}:", path)),
+    }
+}
+
+fn ask_new_passphrase() -> Result<String, String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let passphrase = ask_secret("New passphrase for secrets:")?;
+    if ask_secret("Repeat passphrase:")? != passphrase {
+        return Err("Passphrases didn't match".to_string());
+    }
+    Ok(passphrase)
+}
+
+fn ask_secret(question: &str) -> Result<String, String> {
+    match ui::ask_secret(question) {
+        Ok(Some(answer)) => Ok(answer),
+        Ok(None) => Err("Value is required".to_string()),
+        Err(err) => Err(format!("Couldn't read secret: {}", err)),
+    }
+}
+
+fn read_secrets(path: &str, passphrase: &str) -> Result<BTreeMap<String, String>, String> {
+    let ciphertext = fs::read(path).map_err(|err| format!("Couldn't read {}: {}", path, err))?;
+    let plaintext = decrypt(&ciphertext, passphrase)
+        .map_err(|err| format!("Couldn't decrypt {}: {}", path, err))?;
+    let plaintext = String::from_utf8(plaintext).map_err(|err| err.to_string())?;
+    toml::from_str(&plaintext).map_err(|err| format!("Couldn't parse {}: {}", path, err))
+}
+
+fn write_secrets(
+    path: &str,
+    passphrase: &str,
+    secrets: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    let plaintext = toml::to_string(secrets).map_err(|err| err.to_string())?;
+    let ciphertext = encrypt(plaintext.as_bytes(), passphrase, None)?;
+    fs::write(path, ciphertext).map_err(|err| format!("Couldn't write {}: {}", path, err))
+}
+
+/// Encrypt with age using passphrase. Default work factor of age is used if not given
+fn encrypt(plaintext: &[u8], passphrase: &str, work_factor: Option<u8>) -> Result<Vec<u8>, String> {
+    let mut recipient = age::scrypt::Recipient::new(SecretString::from(passphrase.to_string()));
+    if let Some(work_factor) = work_factor {
+        recipient.set_work_factor(work_factor);
+    }
+    age::encrypt(&recipient, plaintext).map_err(|err| err.to_string())
+}
+
+fn decrypt(ciphertext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+    age::decrypt(&identity, ciphertext).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Config {
+        key: KeySource,
+    }
+
+    #[test]
+    fn reads_key_sources_from_settings() {
+        let key = |toml: &str| toml::from_str::<Config>(toml).unwrap().key;
+        assert_eq!(
+            key("key = \"abc\""),
+            KeySource::Plain(ApiKey::new("abc".to_string()))
+        );
+        assert_eq!(
+            key("key = { env = \"TOGGL_KEY\" }"),
+            KeySource::Env {
+                env: "TOGGL_KEY".to_string()
+            }
+        );
+        assert_eq!(
+            key("key = { command = \"pass show toggl\" }"),
+            KeySource::Command {
+                command: "pass show toggl".to_string()
+            }
+        );
+        assert_eq!(
+            key("key = { secret = \"toggl\" }"),
+            KeySource::Secret {
+                secret: "toggl".to_string()
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn resolves_keys_from_env_and_command() {
+        std::env::set_var("MY_HOURS_TEST_TOGGL_KEY", " abc\n");
+        let env = KeySource::Env {
+            env: "MY_HOURS_TEST_TOGGL_KEY".to_string(),
+        };
+        assert_eq!(env.resolve(), Ok(ApiKey::new("abc".to_string())));
+
+        let command = KeySource::Command {
+            command: "printf 'def\\nuser: me\\n'".to_string(),
+        };
+        assert_eq!(command.resolve(), Ok(ApiKey::new("def".to_string())));
+
+        let failing = KeySource::Command {
+            command: "exit 1".to_string(),
+        };
+        assert!(failing.resolve().is_err());
+        let missing = KeySource::Env {
+            env: "MY_HOURS_TEST_MISSING_KEY".to_string(),
+        };
+        assert!(missing.resolve().is_err());
+    }
+
+    #[test]
+    fn decrypts_only_with_same_passphrase() {
+        let ciphertext = encrypt(b"toggl = \"abc\"\n", "secret", Some(2)).unwrap();
+
+        assert_eq!(
+            decrypt(&ciphertext, "secret").unwrap(),
+            b"toggl = \"abc\"\n"
+        );
+        assert!(decrypt(&ciphertext, "wrong").is_err());
+    }
+}
@@ -10,26 +10,24 @@ static DATE_FORMAT: &str = "%Y-%m-%d";
 
 #[tokio::main]
 /// Get current user's profile
-pub async fn get_me(api_key: &ApiKey) -> types::User {
+pub async fn get_me(api_key: &ApiKey) -> Result<types::User, String> {
     let user: types::User = get("api/v9/me", api_key, &None)
-        .await
-        .unwrap()
+        .await?
         .json()
         .await
-        .unwrap();
-    user
+        .map_err(|err| err.to_string())?;
+    Ok(user)
 }
 
 #[tokio::main]
 /// Get all workspaces where user has access to
-pub async fn get_workspaces(api_key: &ApiKey) -> Vec<types::Workspace> {
+pub async fn get_workspaces(api_key: &ApiKey) -> Result<Vec<types::Workspace>, String> {
     let workspaces: Vec<types::Workspace> = get("api/v9/workspaces", api_key, &None)
-        .await
-        .unwrap()
+        .await?
         .json()
         .await
-        .unwrap();
-    workspaces
+        .map_err(|err| err.to_string())?;
+    Ok(workspaces)
 }
 
 #[tokio::main]
@@ -40,7 +38,7 @@ pub async fn get_time_entries(
     start_date: &NaiveDate,
     end_date: &NaiveDate,
     api_key: &ApiKey,
-) -> Vec<types::TimeEntry> {
+) -> Result<Vec<types::TimeEntry>, String> {
     let mut time_entries = Vec::new();
 
     let mut items_fetch: usize = 0;
@@ -51,11 +49,10 @@ pub async fn get_time_entries(
         let params = time_entries_params(workspace_id, user_id, start_date, end_date, &page);
         let time_entry_response: types::TimeEntryResponse =
             get("reports/api/v2/details", api_key, &params)
-                .await
-                .unwrap()
+                .await?
                 .json()
                 .await
-                .unwrap();
+                .map_err(|err| err.to_string())?;
         time_entries.push(time_entry_response.data.clone());
         items_fetch += time_entry_response.data.len();
         page += 1;
@@ -65,14 +62,14 @@ pub async fn get_time_entries(
         }
     }
 
-    time_entries.concat()
+    Ok(time_entries.concat())
 }
 
 async fn get(
     path: &str,
     api_key: &ApiKey,
     params: &Option<HashMap<String, String>>,
-) -> Result<reqwest::Response, reqwest::Error> {
+) -> Result<reqwest::Response, String> {
     let request_url = api_url(path);
     let mut request = reqwest::Client::new()
         .get(request_url)
@@ -81,9 +78,9 @@ async fn get(
     if params.is_some() {
         request = request.query(&params.as_ref().unwrap());
     }
-    let response = request.send().await?;
+    let response = request.send().await.map_err(|err| err.to_string())?;
 
-    check_status(&response);
+    check_status(&response)?;
     Ok(response)
 }
 
@@ -91,20 +88,18 @@ fn api_url(path: &str) -> String {
     format!("{}/{}", API_URL, path)
 }
 
-fn check_status(response: &reqwest::Response) {
+fn check_status(response: &reqwest::Response) -> Result<(), String> {
     match response.error_for_status_ref() {
-        Ok(_res) => (),
-        Err(err) => {
-            match err.status() {
-                Some(reqwest::StatusCode::FORBIDDEN) => {
-                    println! {"API responded with 403, check your api key."}
-                }
-                _ => {
-                    println! {"API responded with {}, not sure what to do.", err.status().unwrap().as_u16()}
-                }
+        Ok(_res) => Ok(()),
+        Err(err) => match err.status() {
+            Some(reqwest::StatusCode::FORBIDDEN) => {
+                Err("API responded with 403, check your api key.".to_string())
             }
-            std::process::exit(1);
-        }
+            _ => Err(format!(
+                "API responded with {}, not sure what to do.",
+                response.status().as_u16()
+            )),
+        },
     }
 }
 
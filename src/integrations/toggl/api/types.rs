@@ -23,10 +23,14 @@ pub struct TimeEntryResponse {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimeEntry {
     pub id: usize,
+    pub pid: Option<usize>,
     pub description: Option<Description>,
     pub client: Option<ClientName>,
     pub project: Option<ProjectName>,
     pub start: Option<DateTime<Local>>,
     pub end: Option<DateTime<Local>>,
     pub billable: Option<f32>,
+    pub is_billable: Option<bool>,
+    pub tags: Option<Vec<String>>,
+    pub task: Option<String>,
 }
@@ -2,7 +2,9 @@
 //!
 //! API docs: <https://github.com/toggl/toggl_api_docs/blob/master/toggl_api.md>
 
+use crate::hours::types::{ProjectSource, Provider};
 use crate::hours::{self, ui};
+use crate::integrations::secrets::{KeyOptions, KeySource};
 use crate::settings;
 use crate::strict_string::{ApiKey, WorkspaceName, Fullname, Email, ProjectName, Description};
 use chrono::NaiveDate;
@@ -11,7 +13,7 @@ mod api;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
-    pub key: ApiKey,
+    pub key: KeySource,
     pub workspaces: Vec<Workspace>,
     pub user: User,
 }
@@ -31,11 +33,41 @@ pub struct User {
 
 
 
-/// Setup a new toggl integration. You will need an API key, which you can get from your profile page <https://track.toggl.com/profile>
-pub fn setup() {
-    let api_key = ui::ask_input::<String>("Toggl API key:").unwrap().into();
+/// Setup a new toggl integration. You will need an API key, which you can get from your profile page <https://track.toggl.com/profile>.
+/// Key is stored in settings unless it's read from environment variable, command or secrets file
+pub fn setup(key_options: &KeyOptions) {
+    let (api_key, key_source) = match key_options.source() {
+        Some(key_source) => match key_source.resolve() {
+            Ok(api_key) => (api_key, key_source),
+            Err(err) => {
+                println!("Couldn't read Toggl API key: {}", err);
+                return;
+            }
+        },
+        None => match ui::ask_input::<String>("Toggl API key:") {
+            Ok(Some(api_key)) => {
+                let api_key: ApiKey = api_key.into();
+                (api_key.clone(), KeySource::Plain(api_key))
+            }
+            Ok(None) => {
+                println!("API key is required for Toggl integration");
+                return;
+            }
+            Err(err) => {
+                println!("Couldn't read Toggl API key: {}", err);
+                return;
+            }
+        },
+    };
 
-    let workspaces = api::get_workspaces(&api_key)
+    let api_workspaces = match api::get_workspaces(&api_key) {
+        Ok(workspaces) => workspaces,
+        Err(err) => {
+            println!("Couldn't fetch Toggl workspaces: {}", err);
+            return;
+        }
+    };
+    let workspaces = api_workspaces
         .iter()
         .map(|w| Workspace {
             id: w.id,
@@ -43,10 +75,16 @@ pub fn setup() {
         })
         .collect();
 
-    let api_user = api::get_me(&api_key);
+    let api_user = match api::get_me(&api_key) {
+        Ok(user) => user,
+        Err(err) => {
+            println!("Couldn't fetch Toggl user: {}", err);
+            return;
+        }
+    };
 
     let toggl = Config {
-        key: api_key,
+        key: key_source,
         workspaces,
         user: User {
             id: api_user.id,
@@ -74,32 +112,47 @@ pub fn time_entries_for_dates(
     config: &Config,
     start_date: &NaiveDate,
     end_date: &NaiveDate,
-) -> Vec<hours::types::TimeEntry> {
+) -> Result<Vec<hours::types::TimeEntry>, String> {
+    let api_key = config
+        .key
+        .resolve()
+        .map_err(|err| format!("Couldn't read Toggl API key: {}", err))?;
     let workspace_ids: Vec<usize> = config.workspaces.iter().map(|w| w.id).collect();
 
-    let time_entries: Vec<Vec<api::types::TimeEntry>> = workspace_ids
+    let time_entries: Vec<Vec<hours::types::TimeEntry>> = workspace_ids
         .iter()
         .map(|workspace_id| {
-            api::get_time_entries(
+            Ok(api::get_time_entries(
                 workspace_id,
                 &config.user.id,
                 start_date,
                 end_date,
-                &config.key,
-            )
+                &api_key,
+            )?
+            .iter()
+            .map(|api_entry| to_time_entry(api_entry, workspace_id))
+            .collect())
         })
-        .collect();
+        .collect::<Result<_, String>>()?;
 
-    return time_entries
-        .concat()
-        .iter()
-        .map(|api_entry| hours::types::TimeEntry {
-            description: api_entry.description.clone().unwrap_or(Description::new(String::from(""))),
-            client: api_entry.client.clone(),
-            project: api_entry.project.clone().unwrap_or(ProjectName::new(String::from(""))),
-            start: api_entry.start,
-            end: api_entry.end,
-            billable_amount_cents: (api_entry.billable.unwrap_or(0.0) * 100.0) as usize,
-        })
-        .collect();
+    Ok(time_entries.concat())
+}
+
+fn to_time_entry(api_entry: &api::types::TimeEntry, workspace_id: &usize) -> hours::types::TimeEntry {
+    hours::types::TimeEntry {
+        description: api_entry.description.clone().unwrap_or(Description::new(String::from(""))),
+        client: api_entry.client.clone(),
+        project: api_entry.project.clone().unwrap_or(ProjectName::new(String::from(""))),
+        start: api_entry.start,
+        end: api_entry.end,
+        billable_amount_cents: (api_entry.billable.unwrap_or(0.0) * 100.0) as usize,
+        billable: api_entry.is_billable,
+        tags: api_entry.tags.clone().unwrap_or_default(),
+        task: api_entry.task.clone(),
+        project_source: api_entry.pid.map(|project_id| ProjectSource {
+            provider: Provider::Toggl,
+            workspace_id: *workspace_id,
+            project_id,
+        }),
+    }
 }
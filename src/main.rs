@@ -4,12 +4,16 @@
 //!   - Toggl track - <https://track.toggl.com>
 
 #![deny(missing_docs)]
+use chrono::{Local, NaiveDate};
 use clap::Parser;
+use std::path::PathBuf;
 pub mod dates;
 pub mod settings;
 mod hours;
 mod integrations;
+mod projects;
 mod strict_string;
+use strict_string::ClientName;
 
 #[derive(Parser, Debug)]
 #[structopt(name = "My hours")]
@@ -20,9 +24,39 @@ struct Cli {
     /// Refresh hours from integrations before printing them
     #[clap(long)]
     refresh: bool,
+    /// Never prompt for input. New projects are shown as unconfigured
+    #[clap(long)]
+    no_input: bool,
+    /// Group rows of the hours table
+    #[clap(long, value_enum, default_value = "project")]
+    group_by: hours::GroupBy,
+    /// Output format of the hours. JSON never prompts for input
+    #[clap(long, value_enum, default_value = "table")]
+    format: hours::OutputFormat,
+    /// Folder for settings and hours. Defaults to MY_HOURS_HOME or config folder of the platform
+    #[clap(long, global = true)]
+    config_dir: Option<PathBuf>,
+    /// Use separate settings and hours of the profile, e.g. client-a
+    #[clap(long, global = true)]
+    profile: Option<String>,
+    /// When to use colors. Without colors target status is shown with ✓, ! and ✗
+    #[clap(long, value_enum, default_value = "auto", global = true)]
+    color: hours::ui::ColorChoice,
+    /// Columns of the hours table in order, e.g. project,today,month,remaining
+    #[clap(long, value_enum, value_delimiter = ',')]
+    columns: Option<Vec<hours::layout::Column>>,
+    /// Redraw the hours table in place every SECONDS until interrupted
+    #[clap(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "60")]
+    watch: Option<u64>,
+    #[clap(flatten)]
+    filters: hours::Filters,
 }
 
 #[derive(Parser, Debug)]
+#[allow(
+    clippy::enum_variant_names,
+    reason = "IntegrationsCommand is the upstream name, the subcommand is named with clap"
+)]
 enum Command {
     #[clap(name = "integrations")]
     /// Manage integrations
@@ -30,6 +64,98 @@ enum Command {
         #[clap(subcommand)]
         action: integrations::Action,
     },
+    /// Manage API keys in the passphrase-encrypted secrets file
+    Secrets {
+        #[clap(subcommand)]
+        action: integrations::secrets::Action,
+    },
+    /// Manage projects and their targets
+    Projects {
+        #[clap(subcommand)]
+        action: projects::Action,
+    },
+    /// Show project's entries grouped by day and description
+    Show {
+        /// Project name, "Client / Project" or project key
+        project: String,
+        /// Month (YYYY-MM). Defaults to current month
+        #[clap(long, value_parser = dates::parse_month)]
+        month: Option<NaiveDate>,
+    },
+    /// Print one-line status of the stored hours, e.g. for shell prompts. Never refreshes hours
+    Status {
+        /// Format with placeholders like {today}, {target_week} or {remaining_month:Project}
+        #[clap(long, default_value = hours::DEFAULT_STATUS_FORMAT)]
+        format: String,
+    },
+    /// Full-screen dashboard with projects, daily chart and project entries
+    Tui,
+    /// Show calendar of the month with total hours of each day
+    Calendar {
+        /// Month (YYYY-MM). Defaults to current month
+        #[clap(long, value_parser = dates::parse_month)]
+        month: Option<NaiveDate>,
+    },
+    /// Export time entries or summary as CSV or Markdown
+    Export {
+        /// File format
+        #[clap(long, value_enum, default_value = "csv")]
+        format: hours::ExportFormat,
+        /// Export time entries, one row per entry. This is the default
+        #[clap(long, conflicts_with = "summary")]
+        entries: bool,
+        /// Export total hours per project
+        #[clap(long)]
+        summary: bool,
+        /// First date to export (YYYY-MM-DD). Defaults to first day of current month
+        #[clap(long)]
+        from: Option<NaiveDate>,
+        /// Last date to export (YYYY-MM-DD). Defaults to last day of current month
+        #[clap(long)]
+        to: Option<NaiveDate>,
+        /// Write to file instead of stdout
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        #[clap(flatten)]
+        filters: hours::Filters,
+    },
+    /// Render printable HTML timesheet of client's hours for a month
+    Timesheet {
+        /// Client name
+        #[clap(long, required_unless_present = "save_template")]
+        client: Option<String>,
+        /// Month (YYYY-MM). Defaults to current month
+        #[clap(long, value_parser = dates::parse_month)]
+        month: Option<NaiveDate>,
+        /// Write to file instead of stdout
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        /// Save the default template to the settings folder for customizing
+        #[clap(long)]
+        save_template: bool,
+        #[clap(flatten)]
+        filters: hours::ClientFilters,
+    },
+    /// Write invoice draft of client's hours for a month as JSON and HTML
+    Invoice {
+        /// Client name
+        #[clap(long, required_unless_present = "save_template")]
+        client: Option<String>,
+        /// Month (YYYY-MM). Defaults to current month
+        #[clap(long, value_parser = dates::parse_month)]
+        month: Option<NaiveDate>,
+        /// Folder to write the drafts to. Defaults to current folder
+        #[clap(long)]
+        output_dir: Option<PathBuf>,
+        /// Invoice also entries marked as non-billable
+        #[clap(long)]
+        include_non_billable: bool,
+        /// Save the default template to the settings folder for customizing
+        #[clap(long)]
+        save_template: bool,
+        #[clap(flatten)]
+        filters: hours::ClientFilters,
+    },
     /// Refresh hours through integrations
     Refresh,
     /// Show some basic info
@@ -37,15 +163,99 @@ enum Command {
 }
 fn main() {
     let args = Cli::parse();
+    if let Err(err) = settings::use_app_dir(args.config_dir.clone(), args.profile.as_deref()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    if args.no_input {
+        hours::ui::disable_input();
+    }
+    hours::ui::set_color_choice(args.color);
     match &args.command {
         Some(Command::IntegrationsCommand { action }) => integrations::execute(action),
+        Some(Command::Secrets { action }) => integrations::secrets::execute(action),
+        Some(Command::Projects { action }) => projects::execute(action),
+        Some(Command::Show { project, month }) => hours::show_project(&hours::ShowOptions {
+            project: project.clone(),
+            month: month.unwrap_or_else(|| {
+                dates::month_first_and_last_dates(&Local::now().date_naive()).0
+            }),
+        }),
+        Some(Command::Status { format }) => hours::show_status(format),
+        Some(Command::Tui) => hours::run_dashboard(),
+        Some(Command::Calendar { month }) => hours::show_calendar(&month.unwrap_or_else(|| {
+            dates::month_first_and_last_dates(&Local::now().date_naive()).0
+        })),
+        Some(Command::Export {
+            format,
+            summary,
+            from,
+            to,
+            output,
+            filters,
+            ..
+        }) => hours::export(&hours::ExportOptions {
+            format: format.clone(),
+            summary: *summary,
+            from: *from,
+            to: *to,
+            output: output.clone(),
+            filters: filters.clone(),
+        }),
+        Some(Command::Timesheet {
+            save_template: true,
+            ..
+        }) => hours::save_timesheet_template(),
+        Some(Command::Timesheet {
+            client,
+            month,
+            output,
+            filters,
+            ..
+        }) => hours::timesheet(&hours::TimesheetOptions {
+            client: ClientName::new(client.clone().unwrap()),
+            month: month.unwrap_or_else(|| {
+                dates::month_first_and_last_dates(&Local::now().date_naive()).0
+            }),
+            output: output.clone(),
+            filters: filters.clone().into(),
+        }),
+        Some(Command::Invoice {
+            save_template: true,
+            ..
+        }) => hours::save_invoice_template(),
+        Some(Command::Invoice {
+            client,
+            month,
+            output_dir,
+            include_non_billable,
+            filters,
+            ..
+        }) => hours::invoice(&hours::InvoiceOptions {
+            client: ClientName::new(client.clone().unwrap()),
+            month: month.unwrap_or_else(|| {
+                dates::month_first_and_last_dates(&Local::now().date_naive()).0
+            }),
+            output_dir: output_dir.clone(),
+            include_non_billable: *include_non_billable,
+            filters: filters.clone().into(),
+        }),
         Some(Command::Info) => hours::print_info(),
         Some(Command::Refresh) => hours::refresh_all(),
         None => {
             if args.refresh {
                 hours::refresh_all()
             }
-            hours::show_monthly_hours()
+            let options = hours::ViewOptions {
+                group_by: args.group_by,
+                format: args.format,
+                filters: args.filters,
+                columns: args.columns,
+            };
+            match args.watch {
+                Some(interval) => hours::watch_monthly_hours(&options, interval.max(1)),
+                None => hours::show_monthly_hours(&options),
+            }
         }
     }
 }
@@ -0,0 +1,457 @@
+//! Manage project configs
+use crate::hours::{self, target::TargetHours, types::Project};
+use crate::settings::{self, ProjectConfig, ProjectConfigs};
+use crate::strict_string::ProjectHash;
+use clap::Parser;
+use comfy_table::{presets::ASCII_NO_BORDERS, Table};
+use std::collections::HashMap;
+
+#[derive(Parser, Debug)]
+pub enum Action {
+    /// List projects with their keys and targets
+    List {
+        /// List only ignored and archived projects
+        #[clap(long)]
+        hidden: bool,
+    },
+    /// Set targets for project
+    #[clap(group = clap::ArgGroup::new("targets").required(true).multiple(true))]
+    SetTarget {
+        /// Project name, "Client / Project" or project key
+        project: String,
+        /// Target daily hours, e.g. 7h30m or 7.5
+        #[clap(long, group = "targets")]
+        daily: Option<TargetHours>,
+        /// Target weekly hours, e.g. 37h30m or 37.5
+        #[clap(long, group = "targets")]
+        weekly: Option<TargetHours>,
+        /// Target monthly hours, e.g. 150h or 150
+        #[clap(long, group = "targets")]
+        monthly: Option<TargetHours>,
+    },
+    /// Clear targets of project. Clears all targets if none is selected
+    ClearTarget {
+        /// Project name, "Client / Project" or project key
+        project: String,
+        /// Clear daily target
+        #[clap(long)]
+        daily: bool,
+        /// Clear weekly target
+        #[clap(long)]
+        weekly: bool,
+        /// Clear monthly target
+        #[clap(long)]
+        monthly: bool,
+    },
+    /// Leave project out from the hours table and totals
+    Ignore {
+        /// Project name, "Client / Project" or project key
+        project: String,
+    },
+    /// Show ignored project again
+    Unignore {
+        /// Project name, "Client / Project" or project key
+        project: String,
+    },
+    /// Hide project from the hours table, but keep counting it in totals
+    Archive {
+        /// Project name, "Client / Project" or project key
+        project: String,
+    },
+    /// Show archived project again
+    Unarchive {
+        /// Project name, "Client / Project" or project key
+        project: String,
+    },
+    /// Merge project into another one, e.g. after renaming. Hours are combined and targets carried over
+    Merge {
+        /// Project to merge. Name, "Client / Project" or project key
+        from: String,
+        /// Project to merge into. Name, "Client / Project" or project key
+        into: String,
+    },
+}
+
+pub fn execute(action: &Action) {
+    let mut config = settings::load();
+    let projects = hours::stored_time_entries().uniq_projects();
+    let mut project_configs = config.project_configs.clone().unwrap_or(ProjectConfigs {
+        configs: HashMap::new(),
+    });
+    project_configs.migrate_legacy_keys(&projects);
+
+    let project = match action {
+        Action::List { hidden } => {
+            list_projects(&projects, &project_configs, *hidden, &mut std::io::stdout());
+            return;
+        }
+        Action::Merge { from, into } => {
+            let (from, into) = match (
+                find_project_key(from, &projects, &project_configs),
+                find_project_key(into, &projects, &project_configs),
+            ) {
+                (Ok(from), Ok(into)) => (from, into),
+                (Err(err), _) | (_, Err(err)) => exit_with_error(&err),
+            };
+            if project_configs.resolve_key(&into) == from {
+                exit_with_error("Can't merge project into itself");
+            }
+            project_configs.merge(&from, &into);
+            if let Some(project) = projects.iter().find(|project| project.key == into) {
+                project_configs.remember_project(project);
+            }
+            config.project_configs = Some(project_configs);
+            save(&config);
+            return;
+        }
+        Action::SetTarget { project, .. }
+        | Action::ClearTarget { project, .. }
+        | Action::Ignore { project }
+        | Action::Unignore { project }
+        | Action::Archive { project }
+        | Action::Unarchive { project } => project,
+    };
+    let key = match find_project_key(project, &projects, &project_configs) {
+        Ok(key) => project_configs.resolve_key(&key),
+        Err(err) => exit_with_error(&err),
+    };
+    let project_config = project_configs.configs.entry(key).or_default();
+
+    match action {
+        Action::List { .. } | Action::Merge { .. } => unreachable!(),
+        Action::SetTarget {
+            daily,
+            weekly,
+            monthly,
+            ..
+        } => {
+            if daily.is_some() {
+                project_config.target_daily_hours = *daily;
+            }
+            if weekly.is_some() {
+                project_config.target_weekly_hours = *weekly;
+            }
+            if monthly.is_some() {
+                project_config.target_monthly_hours = *monthly;
+            }
+        }
+        Action::ClearTarget {
+            daily,
+            weekly,
+            monthly,
+            ..
+        } => {
+            let all = !daily && !weekly && !monthly;
+            if all || *daily {
+                project_config.target_daily_hours = None;
+            }
+            if all || *weekly {
+                project_config.target_weekly_hours = None;
+            }
+            if all || *monthly {
+                project_config.target_monthly_hours = None;
+            }
+        }
+        Action::Ignore { .. } => project_config.ignored = true,
+        Action::Unignore { .. } => project_config.ignored = false,
+        Action::Archive { .. } => project_config.archived = true,
+        Action::Unarchive { .. } => project_config.archived = false,
+    }
+
+    config.project_configs = Some(project_configs);
+    save(&config);
+}
+
+fn save(config: &settings::Config) {
+    match settings::save(config) {
+        Ok(_) => println!("Project config saved"),
+        Err(err) => println!("Error occured during saving project config: {}", err),
+    }
+}
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
+
+/// Resolve project key by project name, "Client / Project" or key.
+pub fn find_project_key(
+    query: &str,
+    projects: &[Project],
+    project_configs: &ProjectConfigs,
+) -> Result<ProjectHash, String> {
+    let query = query.trim();
+    let key = ProjectHash::new(query);
+    if projects.iter().any(|project| project.key == key)
+        || project_configs.configs.contains_key(&key)
+    {
+        return Ok(key);
+    }
+
+    let matches: Vec<&Project> = projects
+        .iter()
+        .filter(|project| {
+            project.title.as_str().eq_ignore_ascii_case(query)
+                || project_title(project).eq_ignore_ascii_case(query)
+        })
+        .collect();
+    match matches.as_slice() {
+        [project] => Ok(project.key.clone()),
+        [] => Err(format!(
+            "No project found with '{}'. See `my-hours projects list` for projects.",
+            query
+        )),
+        _ => Err(format!(
+            "Multiple projects found with '{}': {}. Use the project key instead.",
+            query,
+            matches
+                .iter()
+                .map(|project| format!("{} ({})", project_title(project), project.key))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// List projects and configs to given writer. With `hidden_only` only ignored and archived projects are listed
+fn list_projects(
+    projects: &[Project],
+    project_configs: &ProjectConfigs,
+    hidden_only: bool,
+    mut writer: impl std::io::Write,
+) {
+    let is_listed = |key: &ProjectHash| {
+        !hidden_only
+            || project_configs
+                .configs
+                .get(key)
+                .is_some_and(|config| config.is_hidden())
+    };
+    let projects: Vec<&Project> = projects
+        .iter()
+        .filter(|project| is_listed(&project.key))
+        .collect();
+    let configs_without_entries: Vec<(&ProjectHash, &ProjectConfig)> = project_configs
+        .configs
+        .iter()
+        .filter(|(key, _)| is_listed(key))
+        .filter(|(key, _)| !projects.iter().any(|project| &project.key == *key))
+        .collect();
+    if projects.is_empty() && configs_without_entries.is_empty() {
+        if hidden_only {
+            writeln!(writer, "No hidden projects found.").unwrap();
+        } else {
+            writeln!(writer, "No projects found.").unwrap();
+        }
+        return;
+    }
+    let mut table = Table::new();
+    table
+        .load_preset(ASCII_NO_BORDERS)
+        .set_header(vec!["Project", "Key", "Target (day / week / month)", "Status"]);
+
+    for project in projects.iter() {
+        table.add_row(project_row(
+            project_title(project),
+            &project.key,
+            project_configs.configs.get(&project.key),
+            &projects,
+        ));
+    }
+    let mut configs_without_entries = configs_without_entries;
+    configs_without_entries.sort_by(|a, b| a.0.partial_cmp(b.0).unwrap());
+    for (key, project_config) in configs_without_entries {
+        table.add_row(project_row(
+            "(no entries this month)".to_string(),
+            key,
+            Some(project_config),
+            &projects,
+        ));
+    }
+    table.force_no_tty();
+    writeln!(writer, "{table}").unwrap();
+}
+
+fn project_row(
+    title: String,
+    key: &ProjectHash,
+    config: Option<&ProjectConfig>,
+    projects: &[&Project],
+) -> Vec<String> {
+    let format_target = |target: Option<TargetHours>| {
+        target
+            .map(|target| target.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let (targets, status) = match config {
+        Some(ProjectConfig {
+            merged_into: Some(merged_into),
+            ..
+        }) => {
+            let merged_into = projects
+                .iter()
+                .find(|project| &project.key == merged_into)
+                .map(|project| project_title(project))
+                .unwrap_or_else(|| merged_into.to_string());
+            ("".to_string(), format!("merged into {}", merged_into))
+        }
+        Some(config) => (
+            format!(
+                "{} / {} / {}",
+                format_target(config.target_daily_hours),
+                format_target(config.target_weekly_hours),
+                format_target(config.target_monthly_hours)
+            ),
+            if config.ignored {
+                "ignored"
+            } else if config.archived {
+                "archived"
+            } else {
+                ""
+            }
+            .to_string(),
+        ),
+        None => ("".to_string(), "unconfigured".to_string()),
+    };
+    vec![title, key.to_string(), targets, status]
+}
+
+fn project_title(project: &Project) -> String {
+    project
+        .client
+        .as_ref()
+        .map(|client| format!("{} / {}", client, project.title))
+        .unwrap_or_else(|| project.title.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strict_string::{ClientName, ProjectName};
+
+    fn project(client: Option<&str>, title: &str, key: &str) -> Project {
+        Project {
+            client: client.map(ClientName::new),
+            title: ProjectName::new(title),
+            key: ProjectHash::new(key),
+            entries: vec![],
+        }
+    }
+
+    fn no_configs() -> ProjectConfigs {
+        ProjectConfigs {
+            configs: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn finds_project_by_name_client_and_key() {
+        let projects = vec![
+            project(Some("Acme"), "Website", "k1"),
+            project(Some("Beta"), "Website", "k2"),
+            project(None, "Internal", "k3"),
+        ];
+
+        assert_eq!(
+            find_project_key("internal", &projects, &no_configs()),
+            Ok(ProjectHash::new("k3"))
+        );
+        assert_eq!(
+            find_project_key("Beta / Website", &projects, &no_configs()),
+            Ok(ProjectHash::new("k2"))
+        );
+        assert_eq!(
+            find_project_key("k1", &projects, &no_configs()),
+            Ok(ProjectHash::new("k1"))
+        );
+        assert!(find_project_key("Website", &projects, &no_configs())
+            .unwrap_err()
+            .starts_with("Multiple projects found"));
+        assert!(find_project_key("Missing", &projects, &no_configs())
+            .unwrap_err()
+            .starts_with("No project found"));
+    }
+
+    #[test]
+    fn lists_projects_with_targets() {
+        let projects = vec![
+            project(Some("Acme"), "Website", "k1"),
+            project(None, "Internal", "k2"),
+        ];
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([
+                (
+                    ProjectHash::new("k1"),
+                    ProjectConfig {
+                        target_daily_hours: Some(TargetHours::from_minutes(450)),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    ProjectHash::new("k3"),
+                    ProjectConfig {
+                        ignored: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    ProjectHash::new("k4"),
+                    ProjectConfig {
+                        merged_into: Some(ProjectHash::new("k1")),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        };
+        let mut result = Vec::new();
+
+        list_projects(&projects, &project_configs, false, &mut result);
+
+        let expected = "
+ Project                 | Key | Target (day / week / month) | Status                     
+==========================================================================================
+ Acme / Website          | k1  | 7h30m / - / -               |                            
+-------------------------+-----+-----------------------------+----------------------------
+ Internal                | k2  |                             | unconfigured               
+-------------------------+-----+-----------------------------+----------------------------
+ (no entries this month) | k3  | - / - / -                   | ignored                    
+-------------------------+-----+-----------------------------+----------------------------
+ (no entries this month) | k4  |                             | merged into Acme / Website 
+";
+        assert_eq!("\n".to_string() + &String::from_utf8(result).unwrap(), expected);
+    }
+
+    #[test]
+    fn lists_only_hidden_projects() {
+        let projects = vec![
+            project(Some("Acme"), "Website", "k1"),
+            project(None, "Internal", "k2"),
+        ];
+        let project_configs = ProjectConfigs {
+            configs: HashMap::from([
+                (ProjectHash::new("k1"), ProjectConfig::default()),
+                (
+                    ProjectHash::new("k2"),
+                    ProjectConfig {
+                        archived: true,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        };
+        let mut result = Vec::new();
+
+        list_projects(&projects, &project_configs, true, &mut result);
+
+        let expected = "
+ Project  | Key | Target (day / week / month) | Status   
+=========================================================
+ Internal | k2  | - / - / -                   | archived 
+";
+        assert_eq!("\n".to_string() + &String::from_utf8(result).unwrap(), expected);
+
+        let mut result = Vec::new();
+        list_projects(&projects, &no_configs(), true, &mut result);
+        assert_eq!(result, b"No hidden projects found.\n");
+    }
+}
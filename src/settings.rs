@@ -1,20 +1,41 @@
 //! Store and load settings
-use crate::hours::types::Project;
+use crate::hours::layout::{Border, Column, DurationFormat};
+use crate::hours::rounding::Rounding;
+use crate::hours::target::TargetHours;
+use crate::hours::types::{Client, Project, ProjectSource};
+use crate::hours::ui;
 use crate::integrations::toggl::Config as TogglConfig;
-use crate::strict_string::ProjectHash;
-use chrono::{DateTime, Local};
+use crate::strict_string::{ClientName, ProjectHash, ProjectName};
+use chrono::{DateTime, Local, NaiveDate};
 use directories_next::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{self, DirBuilder, File};
-use std::path::Path;
-use std::str::FromStr;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, DirBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 static CONFIG_FILENAME: &str = "settings.toml";
+static PROFILES_FOLDER: &str = "profiles";
+/// Environment variable for the settings folder
+pub static HOME_ENV: &str = "MY_HOURS_HOME";
+static APP_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Version of the settings file layout. Increase when adding a step to [`MIGRATIONS`]
+pub const SCHEMA_VERSION: u32 = 1;
+/// Steps upgrading settings from each version to the next one, first step upgrades version 0
+static MIGRATIONS: [fn(&mut toml::Table) -> bool; SCHEMA_VERSION as usize] = [migrate_targets];
+static TARGET_KEYS: [&str; 3] = [
+    "target_daily_hours",
+    "target_weekly_hours",
+    "target_monthly_hours",
+];
 
 /// Configs for the app
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Config {
+    /// Version of the settings layout. Settings without version are from version 0
+    pub schema_version: Option<u32>,
     /// Refresh time entries from integrations if latest entry end time is older than current time + this treshold. Use minutes
     pub refresh_treshold: Option<usize>,
     /// When hours were refreshed last time
@@ -23,6 +44,22 @@ pub struct Config {
     pub toggl: Option<Vec<TogglConfig>>,
     /// Settings for projects
     pub project_configs: Option<ProjectConfigs>,
+    /// Settings for clients
+    pub client_configs: Option<ClientConfigs>,
+    /// Never prompt for input, e.g. when running from cron
+    pub no_input: Option<bool>,
+    /// Currency shown with billable amounts, e.g. EUR
+    pub currency: Option<String>,
+    /// Target share of billable hours from all hours in percents, e.g. 75
+    pub target_billable_percent: Option<u8>,
+    /// Settings for invoice drafts
+    pub invoice: Option<InvoiceConfig>,
+    /// Target daily hours of all projects together. Sum of projects' daily targets is used if not set
+    pub target_daily_hours: Option<TargetHours>,
+    /// Holidays are dimmed in the calendar like weekends
+    pub holidays: Option<Vec<NaiveDate>>,
+    /// Columns and looks of the hours table
+    pub table: Option<TableConfig>,
 }
 
 /// Configs for the projects
@@ -33,62 +70,398 @@ pub struct ProjectConfigs {
 }
 
 /// Single config project
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ProjectConfig {
     /// Target daily hours
-    pub target_daily_hours: Option<u8>,
+    pub target_daily_hours: Option<TargetHours>,
+    /// Target weekly hours
+    pub target_weekly_hours: Option<TargetHours>,
+    /// Target monthly hours
+    pub target_monthly_hours: Option<TargetHours>,
+    /// Ignored projects are left out from the hours table and totals
+    #[serde(default)]
+    pub ignored: bool,
+    /// Archived projects are left out from the hours table rows, but still counted in totals
+    #[serde(default)]
+    pub archived: bool,
+    /// Project has been merged into another project and uses its config
+    pub merged_into: Option<ProjectHash>,
+    /// Hourly rate used when the integration doesn't provide billable amounts
+    pub hourly_rate: Option<f64>,
+    /// Rounding of billed hours, tracked hours are shown without rounding
+    pub rounding: Option<Rounding>,
+    /// Name of the project, saved when other projects are merged into it. Merged hours are shown
+    /// with this name when the project has no hours of its own
+    pub title: Option<ProjectName>,
+    /// Client of the project, saved with the name
+    pub client: Option<ClientName>,
+    /// Project in the integration, saved with the name
+    pub project_source: Option<ProjectSource>,
+}
+
+/// Configs for the clients
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClientConfigs {
+    /// Existing configs
+    pub configs: HashMap<ClientName, ClientConfig>,
+}
+
+/// Single config for client
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// Target daily hours
+    pub target_daily_hours: Option<TargetHours>,
     /// Target weekly hours
-    pub target_weekly_hours: Option<u8>,
+    pub target_weekly_hours: Option<TargetHours>,
     /// Target monthly hours
-    pub target_monthly_hours: Option<u8>,
+    pub target_monthly_hours: Option<TargetHours>,
+    /// Hourly rate for client's projects which don't have their own rate
+    pub hourly_rate: Option<f64>,
+    /// VAT percent of client's invoices, overrides the VAT of invoice settings
+    pub vat_percent: Option<f64>,
+}
+
+/// Settings for invoice drafts
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InvoiceConfig {
+    /// Number of the next invoice, starts from 1
+    pub next_number: Option<u32>,
+    /// Prefix of invoice numbers, e.g. `2022-`
+    pub number_prefix: Option<String>,
+    /// VAT percent added to invoiced amounts, e.g. 24
+    pub vat_percent: Option<f64>,
+    /// Rounding of billed hours of projects which don't have their own rounding
+    pub rounding: Option<Rounding>,
+}
+
+/// Columns and looks of the hours table
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TableConfig {
+    /// Shown columns in order, e.g. `["project", "today", "month", "remaining"]`
+    pub columns: Option<Vec<Column>>,
+    /// `hours-minutes` or `decimal`
+    pub duration_format: Option<DurationFormat>,
+    /// `none`, `ascii`, `utf8` or `markdown`
+    pub border: Option<Border>,
+}
+
+impl InvoiceConfig {
+    /// Number of the next invoice with the prefix
+    pub fn next_invoice_number(&self) -> String {
+        format!(
+            "{}{}",
+            self.number_prefix.clone().unwrap_or_default(),
+            self.next_number.unwrap_or(1)
+        )
+    }
+}
+
+/// Errors when loading settings or hours from a file
+#[derive(Debug)]
+pub enum LoadError {
+    /// Settings folder couldn't be located
+    NoFolder,
+    /// File exists but couldn't be read
+    Io {
+        /// Path of the file
+        path: String,
+        /// Error from the filesystem
+        source: std::io::Error,
+    },
+    /// File isn't valid TOML or some of the values are invalid
+    Parse {
+        /// Path of the file
+        path: String,
+        /// Error from the parser, includes the line or the key which failed
+        message: String,
+    },
+    /// File was written by a newer version of the app
+    NewerVersion {
+        /// Path of the file
+        path: String,
+        /// Schema version of the file
+        version: u32,
+    },
 }
 
-/// Load all settings
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NoFolder => write!(f, "Couldn't locate settings folder"),
+            LoadError::Io { path, source } => write!(f, "Couldn't read {}: {}", path, source),
+            LoadError::Parse { path, message } => {
+                write!(f, "Couldn't parse {}:\n{}", path, message.trim_end())
+            }
+            LoadError::NewerVersion { path, version } => write!(
+                f,
+                "{} has schema version {}, but this version supports up to {}. Update my-hours",
+                path, version, SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Load all settings and exit if they can't be loaded. When the settings file is corrupt, it can be
+/// backed up and settings started from scratch
 pub fn load() -> Config {
-    let settings_path = settings_path().expect("Couldn't load settings");
-    let settings_str = fs::read_to_string(settings_path).expect("Couldn't load settings");
-    toml::from_str(&settings_str).unwrap()
+    match try_load() {
+        Ok(config) => config,
+        Err(err) => {
+            recover_from_load_error(&err);
+            Config::default()
+        }
+    }
+}
+
+/// Load all settings. Settings of older versions are upgraded and saved after backing up the
+/// file. Settings are empty when the settings file doesn't exist yet
+pub fn try_load() -> Result<Config, LoadError> {
+    let settings_path = settings_path()?;
+    let (config, migrated) = read_config(&settings_path)?;
+    if migrated {
+        match backup_file(&settings_path) {
+            Ok(backup) => match save(&config) {
+                Ok(_) => eprintln!(
+                    "Upgraded settings to version {}, old settings are in {}",
+                    SCHEMA_VERSION, backup
+                ),
+                Err(err) => eprintln!("Error occured during upgrading settings: {}", err),
+            },
+            Err(err) => eprintln!("Couldn't back up settings before upgrading: {}", err),
+        }
+    }
+    Ok(config)
+}
+
+/// Read config from the file. Returns also true if settings were upgraded from older version
+fn read_config(path: &str) -> Result<(Config, bool), LoadError> {
+    let settings_str = read_file(path)?;
+    let parse_error = |message: String| LoadError::Parse {
+        path: path.to_string(),
+        message,
+    };
+    let mut settings: toml::Table =
+        toml::from_str(&settings_str).map_err(|err| parse_error(err.to_string()))?;
+    let version = schema_version(&settings).map_err(parse_error)?;
+    if version > SCHEMA_VERSION {
+        return Err(LoadError::NewerVersion {
+            path: path.to_string(),
+            version,
+        });
+    }
+    let migrated = migrate(&mut settings, version);
+    let config: Config = settings
+        .try_into()
+        .map_err(|err: toml::de::Error| parse_error(err.to_string()))?;
+    Ok((config, migrated))
+}
+
+/// Version of the settings layout. Settings without version are from version 0, except empty
+/// settings which are from the current version
+fn schema_version(settings: &toml::Table) -> Result<u32, String> {
+    match settings.get("schema_version") {
+        None if settings.is_empty() => Ok(SCHEMA_VERSION),
+        None => Ok(0),
+        Some(toml::Value::Integer(version)) => u32::try_from(*version)
+            .map_err(|_| format!("Invalid schema_version {}", version)),
+        Some(value) => Err(format!("Invalid schema_version {}", value)),
+    }
+}
+
+/// Run migrations from the given version to the current version. Returns true if any migration
+/// was run
+fn migrate(settings: &mut toml::Table, version: u32) -> bool {
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(settings);
+    }
+    if version < SCHEMA_VERSION {
+        settings.insert(
+            "schema_version".to_string(),
+            toml::Value::Integer(SCHEMA_VERSION.into()),
+        );
+    }
+    version < SCHEMA_VERSION
+}
+
+/// Read file to string. Missing file is read as empty
+pub fn read_file(path: &str) -> Result<String, LoadError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(source) => Err(LoadError::Io {
+            path: path.to_string(),
+            source,
+        }),
+    }
+}
+
+/// Print the error and exit. When the file is corrupt and user confirms, the file is moved to a
+/// backup and the caller can continue with empty data
+pub fn recover_from_load_error(err: &LoadError) {
+    eprintln!("{}", err);
+    if let LoadError::Parse { path, .. } = err {
+        if ui::confirm("Back up the file and start fresh?") {
+            let backup = backup_path(path);
+            match fs::rename(path, &backup) {
+                Ok(_) => {
+                    eprintln!("Backed up {} to {}", path, backup);
+                    return;
+                }
+                Err(err) => eprintln!("Couldn't back up {}: {}", path, err),
+            }
+        } else {
+            eprintln!("Fix or remove the file and try again");
+        }
+    }
+    std::process::exit(1);
+}
+
+/// Copy file next to the original with a timestamp in the name. Returns the path of the backup
+pub fn backup_file(path: &str) -> Result<String, std::io::Error> {
+    let backup = backup_path(path);
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+fn backup_path(path: &str) -> String {
+    format!("{}.backup-{}", path, Local::now().format("%Y%m%d%H%M%S"))
 }
 
 /// Store config to filestystem
 pub fn save(config: &Config) -> Result<(), std::io::Error> {
-    let settings_path =
-        settings_path().unwrap_or_else(|| panic!("Failed to locate {}", CONFIG_FILENAME));
+    let settings_path = settings_path().map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Failed to locate {}: {}", CONFIG_FILENAME, err),
+        )
+    })?;
+    let config = Config {
+        schema_version: Some(SCHEMA_VERSION),
+        ..config.clone()
+    };
     let toml = toml::to_string(&config).unwrap();
     fs::write(settings_path, toml)
 }
 
-/// Mark hours as refreshed
-pub fn hours_refreshed() {
-    let mut settings = load();
+/// Mark hours as refreshed. Doesn't ask anything when the settings can't be loaded
+pub fn hours_refreshed() -> Result<(), String> {
+    let mut settings = try_load().map_err(|err| err.to_string())?;
     settings.refreshed_at = Some(Local::now());
-    match save(&settings) {
-        Ok(_) => {}
-        Err(err) => println!("Error occured during refreshing hours: {}", err),
-    };
+    save(&settings).map_err(|err| format!("Error occured during refreshing hours: {}", err))
 }
-/// Get path to a file in app's folder. If file doesn't exist, it will be created
-pub fn app_path(file: &str) -> Option<String> {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "Piece Of Code", "Hours") {
-        if !proj_dirs.config_dir().exists() {
-            DirBuilder::new()
-                .recursive(true)
-                .create(proj_dirs.config_dir())
-                .unwrap()
-        }
-        let folder = proj_dirs.config_dir().to_str().unwrap();
-        let settings_file_path = format!("{}/{}", String::from_str(folder).unwrap(), file);
-        if !Path::new(&settings_file_path).exists() {
-            File::create(&settings_file_path)
-                .unwrap_or_else(|_| panic!("Couldn't create settings file {}", settings_file_path));
+/// Get path to a file in app's folder without creating the file. The folder is created if needed
+pub fn app_file_path(file: &str) -> Result<String, LoadError> {
+    let folder = app_dir().ok_or(LoadError::NoFolder)?;
+    create_folder(&folder).map_err(|source| LoadError::Io {
+        path: folder.display().to_string(),
+        source,
+    })?;
+    folder
+        .join(file)
+        .to_str()
+        .map(|path| path.to_string())
+        .ok_or(LoadError::NoFolder)
+}
+
+fn create_folder(folder: &Path) -> Result<(), std::io::Error> {
+    if folder.exists() {
+        return Ok(());
+    }
+    DirBuilder::new().recursive(true).create(folder)
+}
+
+/// Use another settings folder and profile during this run. Folder given here overrides the
+/// [`HOME_ENV`] environment variable. Settings and hours of a profile are kept in their own
+/// folder under the settings folder. Has to be called before settings are used. Fails if the
+/// folder can't be created
+pub fn use_app_dir(config_dir: Option<PathBuf>, profile: Option<&str>) -> Result<(), String> {
+    let folder = resolve_app_dir(config_dir, std::env::var_os(HOME_ENV), default_dir(), profile)?;
+    create_folder(&folder).map_err(|err| {
+        format!("Couldn't create settings folder {}: {}", folder.display(), err)
+    })?;
+    APP_DIR
+        .set(folder)
+        .map_err(|_| "Settings folder is already in use".to_string())
+}
+
+/// Folder where settings and hours are stored
+pub fn app_dir() -> Option<PathBuf> {
+    match APP_DIR.get() {
+        Some(folder) => Some(folder.clone()),
+        None => resolve_app_dir(None, std::env::var_os(HOME_ENV), default_dir(), None).ok(),
+    }
+}
+
+fn default_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "Piece Of Code", "Hours")
+        .map(|proj_dirs| proj_dirs.config_dir().to_path_buf())
+}
+
+fn resolve_app_dir(
+    config_dir: Option<PathBuf>,
+    home_env: Option<OsString>,
+    default_dir: Option<PathBuf>,
+    profile: Option<&str>,
+) -> Result<PathBuf, String> {
+    let home_env = home_env.filter(|home| !home.is_empty()).map(PathBuf::from);
+    let folder = config_dir
+        .or(home_env)
+        .or(default_dir)
+        .ok_or_else(|| "Couldn't locate settings folder".to_string())?;
+    match profile {
+        None => Ok(folder),
+        Some(profile)
+            if !profile.is_empty()
+                && profile
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            Ok(folder.join(PROFILES_FOLDER).join(profile))
         }
-        return Some(settings_file_path);
-    };
-    None
+        Some(profile) => Err(format!(
+            "Invalid profile name '{}'. Use letters, numbers, '-' and '_'",
+            profile
+        )),
+    }
 }
+
 /// Path for settings
-pub fn settings_path() -> Option<String> {
-    app_path(CONFIG_FILENAME)
+pub fn settings_path() -> Result<String, LoadError> {
+    app_file_path(CONFIG_FILENAME)
+}
+
+/// Migration from version 0: convert targets stored as numbers of hours into target strings.
+/// Returns true if anything changed
+fn migrate_targets(settings: &mut toml::Table) -> bool {
+    let mut migrated = false;
+    for configs_key in ["project_configs", "client_configs"] {
+        let configs = settings
+            .get_mut(configs_key)
+            .and_then(|configs| configs.get_mut("configs"))
+            .and_then(|configs| configs.as_table_mut());
+        for (_, config) in configs.into_iter().flat_map(|configs| configs.iter_mut()) {
+            for target_key in TARGET_KEYS {
+                if config.get_mut(target_key).is_some_and(migrate_target) {
+                    migrated = true;
+                }
+            }
+        }
+    }
+    migrated
+}
+
+/// Convert target given as number of hours to string. Returns true if the target was converted
+fn migrate_target(target: &mut toml::Value) -> bool {
+    let hours = match target {
+        toml::Value::Integer(hours) => *hours as f64,
+        toml::Value::Float(hours) => *hours,
+        _ => return false,
+    };
+    let minutes = (hours * 60.0).round().max(0.0) as u32;
+    *target = toml::Value::String(TargetHours::from_minutes(minutes).to_string());
+    true
 }
 
 impl Config {
@@ -101,7 +474,103 @@ impl Config {
 impl ProjectConfigs {
     /// Get config for project
     pub fn get(&self, project: &Project) -> Option<&ProjectConfig> {
-        self.configs.get(&project.key)
+        self.get_by_key(&project.key)
+    }
+
+    /// Get config for project key. Merged projects get the config of the project they were merged into
+    pub fn get_by_key(&self, key: &ProjectHash) -> Option<&ProjectConfig> {
+        self.configs.get(&self.resolve_key(key))
+    }
+
+    /// Follow merges to the key of the project where given project was merged into
+    pub fn resolve_key(&self, key: &ProjectHash) -> ProjectHash {
+        let mut resolved = key.clone();
+        for _ in 0..self.configs.len() {
+            match self.configs.get(&resolved).and_then(|c| c.merged_into.as_ref()) {
+                Some(merged_into) if merged_into != key => resolved = merged_into.clone(),
+                _ => break,
+            }
+        }
+        resolved
+    }
+
+    /// Returns true if project with given key is ignored
+    pub fn is_ignored(&self, key: &ProjectHash) -> bool {
+        self.get_by_key(key).is_some_and(|config| config.ignored)
+    }
+
+    /// Returns true if project with given key is archived
+    pub fn is_archived(&self, key: &ProjectHash) -> bool {
+        self.get_by_key(key).is_some_and(|config| config.archived)
+    }
+
+    /// Copy configs stored with name based keys to projects which are now identified by their
+    /// integration id. Returns true if any config was migrated
+    pub fn migrate_legacy_keys(&mut self, projects: &[Project]) -> bool {
+        let mut migrated_keys = Vec::new();
+        for project in projects {
+            let legacy_key = project.legacy_key();
+            if legacy_key == project.key || self.configs.contains_key(&project.key) {
+                continue;
+            }
+            if let Some(legacy_config) = self.configs.get(&legacy_key).cloned() {
+                self.configs.insert(project.key.clone(), legacy_config);
+                migrated_keys.push(legacy_key);
+            }
+        }
+        for legacy_key in migrated_keys.iter() {
+            if !projects.iter().any(|project| &project.key == legacy_key) {
+                self.configs.remove(legacy_key);
+            }
+        }
+        !migrated_keys.is_empty()
+    }
+
+    /// Merge project into another one. Targets of the merged project are carried over when
+    /// the other project doesn't have them
+    pub fn merge(&mut self, from: &ProjectHash, into: &ProjectHash) {
+        let from_config = self.configs.remove(from).unwrap_or_default();
+        let into_config = self.configs.entry(into.clone()).or_default();
+        into_config.merged_into = None;
+        into_config.target_daily_hours = into_config
+            .target_daily_hours
+            .or(from_config.target_daily_hours);
+        into_config.target_weekly_hours = into_config
+            .target_weekly_hours
+            .or(from_config.target_weekly_hours);
+        into_config.target_monthly_hours = into_config
+            .target_monthly_hours
+            .or(from_config.target_monthly_hours);
+        self.configs.insert(
+            from.clone(),
+            ProjectConfig {
+                merged_into: Some(into.clone()),
+                ignored: from_config.ignored,
+                archived: from_config.archived,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Save name, client and source of the project to its config
+    pub fn remember_project(&mut self, project: &Project) {
+        let config = self.configs.entry(project.key.clone()).or_default();
+        config.title = Some(project.title.clone());
+        config.client = project.client.clone();
+        config.project_source = project
+            .entries
+            .first()
+            .and_then(|entry| entry.project_source.clone());
+    }
+}
+
+impl ClientConfigs {
+    /// Get config for client
+    pub fn get(&self, client: &Client) -> Option<&ClientConfig> {
+        client
+            .name
+            .as_ref()
+            .and_then(|name| self.configs.get(name))
     }
 }
 
@@ -112,4 +581,267 @@ impl ProjectConfig {
             || self.target_weekly_hours.is_some()
             || self.target_monthly_hours.is_some()
     }
+
+    /// Returns true if project is not shown in the hours table
+    pub fn is_hidden(&self) -> bool {
+        self.ignored || self.archived
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hours::types::{ProjectSource, Provider, TimeEntry};
+    use crate::strict_string::ProjectName;
+
+    fn project_with_source(title: &str, project_id: usize) -> Project {
+        let entry = TimeEntry {
+            project: ProjectName::new(title),
+            project_source: Some(ProjectSource {
+                provider: Provider::Toggl,
+                workspace_id: 1,
+                project_id,
+            }),
+            ..Default::default()
+        };
+        Project {
+            client: None,
+            title: entry.project.clone(),
+            key: entry.project_key(),
+            entries: vec![entry],
+        }
+    }
+
+    fn daily_target(hours: u32) -> ProjectConfig {
+        ProjectConfig {
+            target_daily_hours: Some(TargetHours::from_hours(hours)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn migrates_configs_from_legacy_keys() {
+        let first = project_with_source("Maintenance", 1);
+        let second = project_with_source("Maintenance", 2);
+        let mut project_configs = ProjectConfigs {
+            configs: HashMap::from([(first.legacy_key(), daily_target(4))]),
+        };
+
+        assert!(project_configs.migrate_legacy_keys(&[first.clone(), second.clone()]));
+
+        assert_eq!(project_configs.configs.len(), 2);
+        assert_eq!(
+            project_configs.get(&first).unwrap().target_daily_hours,
+            Some(TargetHours::from_hours(4))
+        );
+        assert!(project_configs.get(&second).is_some());
+        assert!(!project_configs.migrate_legacy_keys(&[first, second]));
+    }
+
+    #[test]
+    fn merged_projects_use_config_of_target_project() {
+        let old = ProjectHash::new("old");
+        let new = ProjectHash::new("new");
+        let mut project_configs = ProjectConfigs {
+            configs: HashMap::from([
+                (
+                    old.clone(),
+                    ProjectConfig {
+                        archived: true,
+                        ..daily_target(6)
+                    },
+                ),
+                (
+                    new.clone(),
+                    ProjectConfig {
+                        target_monthly_hours: Some(TargetHours::from_hours(100)),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        };
+
+        project_configs.merge(&old, &new);
+
+        assert_eq!(project_configs.resolve_key(&old), new);
+        let config = project_configs.get_by_key(&old).unwrap();
+        assert_eq!(config.target_daily_hours, Some(TargetHours::from_hours(6)));
+        assert_eq!(config.target_monthly_hours, Some(TargetHours::from_hours(100)));
+        assert_eq!(project_configs.configs[&old].merged_into, Some(new.clone()));
+        assert!(project_configs.configs[&old].archived);
+        assert!(!project_configs.is_archived(&old));
+
+        project_configs.merge(&new, &old);
+        assert_eq!(project_configs.resolve_key(&old), old);
+        assert_eq!(project_configs.resolve_key(&new), old);
+    }
+
+    #[test]
+    fn migrates_numeric_targets() {
+        let mut settings: toml::Table = toml::from_str(
+            r#"
+            [project_configs.configs.abc]
+            target_daily_hours = 8
+            target_weekly_hours = 37.5
+            target_monthly_hours = "160h"
+
+            [client_configs.configs.Acme]
+            target_monthly_hours = 300
+            "#,
+        )
+        .unwrap();
+
+        assert!(migrate_targets(&mut settings));
+        let config: Config = settings.clone().try_into().unwrap();
+        let project_config = config.project_configs.unwrap().configs[&ProjectHash::new("abc")].clone();
+        assert_eq!(project_config.target_daily_hours, Some(TargetHours::from_hours(8)));
+        assert_eq!(project_config.target_weekly_hours, Some(TargetHours::from_minutes(2250)));
+        assert_eq!(project_config.target_monthly_hours, Some(TargetHours::from_hours(160)));
+        let client_config = config.client_configs.unwrap().configs[&ClientName::new("Acme")].clone();
+        assert_eq!(client_config.target_monthly_hours, Some(TargetHours::from_hours(300)));
+        assert_eq!(client_config.target_daily_hours, None);
+
+        assert!(!migrate_targets(&mut settings));
+    }
+
+    #[test]
+    fn upgrades_settings_of_version_0() {
+        let path = "/tmp/my-hours-settings-v0.toml";
+        fs::write(
+            path,
+            r#"
+            refresh_treshold = 180
+
+            [project_configs.configs.abc]
+            target_daily_hours = 6
+            target_monthly_hours = 120
+            "#,
+        )
+        .unwrap();
+
+        let (config, migrated) = read_config(path).unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.schema_version, Some(SCHEMA_VERSION));
+        assert_eq!(config.refresh_treshold, Some(180));
+        let project_config = config.project_configs.unwrap().configs[&ProjectHash::new("abc")].clone();
+        assert_eq!(project_config.target_daily_hours, Some(TargetHours::from_hours(6)));
+        assert_eq!(project_config.target_monthly_hours, Some(TargetHours::from_hours(120)));
+    }
+
+    #[test]
+    fn reads_settings_of_version_1_as_is() {
+        let path = "/tmp/my-hours-settings-v1.toml";
+        fs::write(
+            path,
+            "schema_version = 1\ntarget_daily_hours = \"7h 30m\"\n[table]\nborder = \"utf8\"\n",
+        )
+        .unwrap();
+
+        let (config, migrated) = read_config(path).unwrap();
+
+        assert!(!migrated);
+        assert_eq!(config.target_daily_hours, Some(TargetHours::from_minutes(450)));
+        assert_eq!(config.table.unwrap().border, Some(Border::Utf8));
+    }
+
+    #[test]
+    fn refuses_settings_of_newer_or_invalid_version() {
+        let path = "/tmp/my-hours-settings-newer.toml";
+        fs::write(path, format!("schema_version = {}\n", SCHEMA_VERSION + 1)).unwrap();
+        assert!(matches!(
+            read_config(path),
+            Err(LoadError::NewerVersion { version, .. }) if version == SCHEMA_VERSION + 1
+        ));
+
+        let path = "/tmp/my-hours-settings-invalid-version.toml";
+        fs::write(path, "schema_version = \"one\"\n").unwrap();
+        let err = read_config(path).unwrap_err().to_string();
+        assert!(err.contains("Invalid schema_version \"one\""), "{err}");
+    }
+
+    #[test]
+    fn treats_current_and_empty_settings_as_up_to_date() {
+        let config = Config {
+            schema_version: Some(SCHEMA_VERSION),
+            ..Default::default()
+        };
+        let mut settings: toml::Table = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+
+        assert_eq!(schema_version(&settings), Ok(SCHEMA_VERSION));
+        assert!(!migrate(&mut settings, SCHEMA_VERSION));
+        assert_eq!(schema_version(&toml::Table::new()), Ok(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn resolves_app_dir_with_overrides_and_profiles() {
+        let default_dir = Some(PathBuf::from("/default"));
+        let home = Some(OsString::from("/home"));
+
+        assert_eq!(
+            resolve_app_dir(None, None, default_dir.clone(), None),
+            Ok(PathBuf::from("/default"))
+        );
+        assert_eq!(
+            resolve_app_dir(None, Some(OsString::new()), default_dir.clone(), None),
+            Ok(PathBuf::from("/default"))
+        );
+        assert_eq!(
+            resolve_app_dir(None, home.clone(), default_dir.clone(), None),
+            Ok(PathBuf::from("/home"))
+        );
+        assert_eq!(
+            resolve_app_dir(
+                Some(PathBuf::from("/dir")),
+                home,
+                default_dir.clone(),
+                Some("client-a")
+            ),
+            Ok(PathBuf::from("/dir/profiles/client-a"))
+        );
+        assert!(resolve_app_dir(None, None, default_dir, Some("../other")).is_err());
+        assert!(resolve_app_dir(None, None, None, None).is_err());
+    }
+
+    #[test]
+    fn reads_missing_settings_as_empty() {
+        let (config, migrated) = read_config("/tmp/my-hours-missing-settings.toml").unwrap();
+        assert!(config.toggl.is_none());
+        assert!(!migrated);
+    }
+
+    #[test]
+    fn tells_which_key_failed_to_parse() {
+        let path = "/tmp/my-hours-invalid-settings.toml";
+        fs::write(path, "[project_configs.configs.abc]\ntarget_daily_hours = \"x8\"\n").unwrap();
+
+        let err = read_config(path).unwrap_err().to_string();
+
+        assert!(err.starts_with("Couldn't parse /tmp/my-hours-invalid-settings.toml"), "{err}");
+        assert!(err.contains("'x8' is not a valid target"), "{err}");
+        assert!(err.contains("project_configs.configs.abc.target_daily_hours"), "{err}");
+    }
+
+    #[test]
+    fn backs_up_file_next_to_original() {
+        let path = "/tmp/my-hours-backup-settings.toml";
+        fs::write(path, "no_input = true\n").unwrap();
+
+        let backup = backup_file(path).unwrap();
+
+        assert!(backup.starts_with("/tmp/my-hours-backup-settings.toml.backup-"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "no_input = true\n");
+        fs::remove_file(backup).unwrap();
+    }
+
+    #[test]
+    fn fails_to_create_folder_under_file() {
+        let path = "/tmp/my-hours-folder-file";
+        fs::write(path, "").unwrap();
+
+        let result = create_folder(&Path::new(path).join("settings"));
+
+        assert!(result.is_err());
+        fs::remove_file(path).unwrap();
+    }
 }
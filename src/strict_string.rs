@@ -13,7 +13,7 @@ use std::hash::Hash;
 #[macro_export]
 macro_rules! strict_string {
     ( $name:ident) => {
-        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+        #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Hash)]
         pub struct $name(String);
 
         impl $name {
@@ -0,0 +1,90 @@
+//! Stored entries without an end time are running timers and every command counts them until now
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use chrono::{Local, SecondsFormat};
+
+/// Config folder with a running timer stored and hours refreshed recently enough to not refresh
+fn config_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("my-hours-running-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let now = Local::now().to_rfc3339_opts(SecondsFormat::Secs, false);
+    fs::write(
+        dir.join("settings.toml"),
+        format!(
+            "schema_version = 1\nrefresh_treshold = 100000\nrefreshed_at = \"{}\"\n\
+             no_input = true\n",
+            now
+        ),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("hours.toml"),
+        format!(
+            "[[entries]]\ndescription = \"Running\"\nclient = \"Acme\"\nproject = \"Website\"\n\
+             start = \"{}\"\nbillable_amount_cents = 0\n",
+            now
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+fn run(name: &str, args: &[&str]) -> Output {
+    let dir = config_dir(name);
+    let output = Command::new(env!("CARGO_BIN_EXE_my-hours"))
+        .arg("--config-dir")
+        .arg(&dir)
+        .args(args)
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+fn assert_success(output: Output) {
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn shows_hours_table() {
+    assert_success(run("table", &[]));
+}
+
+#[test]
+fn prints_status() {
+    assert_success(run("status", &["status"]));
+}
+
+#[test]
+fn shows_calendar() {
+    assert_success(run("calendar", &["calendar"]));
+}
+
+#[test]
+fn shows_project() {
+    assert_success(run("show", &["show", "Website"]));
+}
+
+#[test]
+fn exports_entries() {
+    assert_success(run("export", &["export"]));
+}
+
+#[test]
+fn renders_timesheet() {
+    assert_success(run("timesheet", &["timesheet", "--client", "Acme"]));
+}
+
+#[test]
+fn writes_invoice() {
+    assert_success(run("invoice", &["invoice", "--client", "Acme"]));
+}